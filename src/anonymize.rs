@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use regex::Regex;
+use crate::event_log::EventRecord;
+
+/// EventData fields whose values identify a user, computer or domain
+const USER_FIELDS: [&str; 3] = ["SubjectUserName", "TargetUserName", "TargetOutboundUserName"];
+const HOST_FIELDS: [&str; 3] = ["WorkstationName", "Workstation", "TargetServerName"];
+const DOMAIN_FIELDS: [&str; 3] = ["SubjectDomainName", "TargetDomainName", "TargetOutboundDomainName"];
+
+/// Built-in accounts that say nothing about the organization and stay readable
+const KEEP_VALUES: [&str; 7] = ["-", "SYSTEM", "LOCAL SERVICE", "NETWORK SERVICE", "ANONYMOUS LOGON", "NT AUTHORITY", "127.0.0.1"];
+
+/// Replaces usernames, domain SIDs, IPv4 addresses, computer and domain names
+/// with stable pseudonyms (USER_1, HOST_2, ...). The same value gets the same
+/// pseudonym for as long as the Anonymizer lives, across every export.
+pub struct Anonymizer {
+    pseudonyms: HashMap<String, String>,
+    counters: HashMap<&'static str, usize>,
+    sid: Regex,
+    /// Any whole SID, well-known ones included, which is never a user name
+    any_sid: Regex,
+    ipv4: Regex,
+}
+
+impl Default for Anonymizer {
+    fn default() -> Self {
+        Self {
+            pseudonyms: HashMap::new(),
+            counters: HashMap::new(),
+            // Only domain and local account SIDs; well-known ones like S-1-5-18 stay
+            sid: Regex::new(r"S-1-5-21(?:-\d+){3,4}").unwrap(),
+            any_sid: Regex::new(r"^S-1-\d+(?:-\d+)*$").unwrap(),
+            ipv4: Regex::new(r"\b(?:\d{1,3}\.){3}\d{1,3}\b").unwrap(),
+        }
+    }
+}
+
+impl Anonymizer {
+    /// Copies of `events` with every sensitive value replaced in all text fields, raw_xml included
+    pub fn anonymize(&mut self, events: &[EventRecord]) -> Vec<EventRecord> {
+        events.iter().map(|ev| self.anonymize_event(ev)).collect()
+    }
+
+    /// Original values and their pseudonyms, sorted by pseudonym
+    pub fn mapping(&self) -> Vec<(String, String)> {
+        let mut pairs: Vec<_> = self.pseudonyms.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        pairs.sort_by(|a, b| a.1.cmp(&b.1));
+        pairs
+    }
+
+    fn anonymize_event(&mut self, ev: &EventRecord) -> EventRecord {
+        let mut found: Vec<(String, &'static str)> = Vec::new();
+        // Domain SIDs in the user field are picked up by the SID scan below
+        if !self.any_sid.is_match(&ev.user) {
+            match ev.user.split_once('\\') {
+                Some((domain, user)) => {
+                    found.push((domain.to_string(), "DOMAIN"));
+                    found.push((user.to_string(), "USER"));
+                }
+                None => found.push((ev.user.clone(), "USER")),
+            }
+        }
+        found.push((ev.computer.clone(), "HOST"));
+        for (name, value) in &ev.data {
+            let kind = if USER_FIELDS.contains(&name.as_str()) {
+                "USER"
+            } else if HOST_FIELDS.contains(&name.as_str()) {
+                "HOST"
+            } else if DOMAIN_FIELDS.contains(&name.as_str()) {
+                "DOMAIN"
+            } else {
+                continue;
+            };
+            found.push((value.trim_start_matches('\\').to_string(), kind));
+        }
+        let texts = [&ev.user, &ev.raw_xml, &ev.description].into_iter().chain(ev.data.iter().map(|(_, v)| v));
+        for text in texts {
+            found.extend(self.sid.find_iter(text).map(|m| (m.as_str().to_string(), "SID")));
+            found.extend(self.ipv4.find_iter(text).map(|m| (m.as_str().to_string(), "IP")));
+        }
+        for (value, kind) in found {
+            if value.len() < 2 || KEEP_VALUES.iter().any(|k| k.eq_ignore_ascii_case(&value)) {
+                continue;
+            }
+            if !self.pseudonyms.contains_key(&value) {
+                let n = self.counters.entry(kind).or_default();
+                *n += 1;
+                self.pseudonyms.insert(value, format!("{}_{}", kind, n));
+            }
+        }
+        // The bare host name of an FQDN shares its pseudonym
+        if let (Some((short, _)), Some(pseudonym)) = (ev.computer.split_once('.'), self.pseudonyms.get(&ev.computer)) {
+            let pseudonym = pseudonym.clone();
+            self.pseudonyms.entry(short.to_string()).or_insert(pseudonym);
+        }
+        let mut out = ev.clone();
+        for field in [&mut out.user, &mut out.computer, &mut out.description, &mut out.raw_xml] {
+            *field = self.replace_all(field);
+        }
+        for (_, value) in &mut out.data {
+            *value = self.replace_all(value);
+        }
+        out
+    }
+
+    /// Replaces every known value that appears as a whole word in `text`,
+    /// longest first so "host.corp.local" wins over "host"
+    fn replace_all(&self, text: &str) -> String {
+        let mut values: Vec<&String> = self.pseudonyms.keys().filter(|v| text.contains(v.as_str())).collect();
+        values.sort_by_key(|v| std::cmp::Reverse(v.len()));
+        let mut out = text.to_string();
+        for value in values {
+            out = replace_word(&out, value, &self.pseudonyms[value]);
+        }
+        out
+    }
+}
+
+/// Replaces occurrences of `from` not embedded in a longer alphanumeric word
+fn replace_word(text: &str, from: &str, to: &str) -> String {
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find(from) {
+        let before = if pos > 0 { rest[..pos].chars().next_back() } else { out.chars().next_back() };
+        let after = rest[pos + from.len()..].chars().next();
+        out.push_str(&rest[..pos]);
+        if is_word(before) || is_word(after) {
+            out.push_str(from);
+        } else {
+            out.push_str(to);
+        }
+        rest = &rest[pos + from.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_well_known_sids() {
+        let ev = EventRecord {
+            user: "S-1-5-18".to_string(),
+            computer: "DC01.corp.example".to_string(),
+            data: vec![("SubjectUserSid".to_string(), "S-1-5-18".to_string()), ("TargetUserName".to_string(), "alice".to_string())],
+            raw_xml: "<Security UserID='S-1-5-18'/><Data Name='TargetUserName'>alice</Data>".to_string(),
+            ..Default::default()
+        };
+        let mut anonymizer = Anonymizer::default();
+        let out = anonymizer.anonymize(&[ev]).remove(0);
+        assert_eq!(out.user, "S-1-5-18");
+        assert_eq!(out.data[0].1, "S-1-5-18");
+        assert_eq!(out.data[1].1, "USER_1");
+        assert_eq!(out.raw_xml, "<Security UserID='S-1-5-18'/><Data Name='TargetUserName'>USER_1</Data>");
+        assert!(anonymizer.mapping().iter().all(|(original, _)| original != "S-1-5-18"));
+    }
+}
@@ -1,23 +1,510 @@
 use chrono::{DateTime, Local};
 use quick_xml::Reader;
-use quick_xml::events::Event as XmlEvent;
-use std::process::Command;
+use quick_xml::events::{BytesStart, Event as XmlEvent};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Mutex, OnceLock, RwLock};
+#[cfg(target_os = "windows")]
+use std::io::Read;
+#[cfg(target_os = "windows")]
+use std::process::{Command, Stdio};
+#[cfg(not(target_os = "windows"))]
 use std::fs::File;
+#[cfg(not(target_os = "windows"))]
 use std::io::{BufRead, BufReader};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct EventRecord {
     pub log_name: String,
+    /// EventRecordID, unique within a channel; 0 when the source has none
+    pub record_id: u64,
     pub time_created: DateTime<Local>,
     pub event_id: u16,
     pub level: String,
+    /// Level number as written in the XML (2 = Error, ...); `level` holds its name
+    pub level_num: u8,
+    pub opcode: u8,
+    pub version: u8,
+    pub task: u16,
+    /// Keywords bitmask as written in the XML, e.g. "0x8020000000000000"
+    pub keywords: String,
+    /// Correlation ActivityID, empty when absent
+    pub activity_id: String,
+    pub process_id: u32,
+    pub thread_id: u32,
     pub source: String,
+    /// Provider/@Guid, for providers whose Name is generic or shared
+    pub provider_guid: Option<String>,
     pub user: String,
     pub computer: String,
     pub description: String,
+    /// Named `<Data>` values from EventData, or UserData fields, in document order
+    pub data: Vec<(String, String)>,
+    /// Hex-encoded `<Binary>` payload from EventData, empty when absent
+    pub binary: String,
+    /// Provider-formatted strings, present when wevtutil was asked for RenderedXml
+    pub rendering: Option<RenderingInfo>,
+    /// Display names filled in once by resolve_names, so the UI doesn't look them up
+    /// every frame; empty for records that never went through it
+    pub task_display: String,
+    pub opcode_display: String,
+    pub keyword_names: Vec<String>,
     pub raw_xml: String,
 }
 
+/// The `<RenderingInfo>` block of /f:RenderedXml output: the provider's own
+/// message and display names, in the system's language
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct RenderingInfo {
+    pub message: String,
+    pub level: String,
+    pub task: String,
+    pub opcode: String,
+    pub keywords: Vec<String>,
+}
+
+impl Default for EventRecord {
+    fn default() -> Self {
+        Self {
+            log_name: String::new(),
+            record_id: 0,
+            time_created: Local::now(),
+            event_id: 0,
+            level: String::new(),
+            level_num: 0,
+            opcode: 0,
+            version: 0,
+            task: 0,
+            keywords: String::new(),
+            activity_id: String::new(),
+            process_id: 0,
+            thread_id: 0,
+            source: String::new(),
+            provider_guid: None,
+            user: String::new(),
+            computer: String::new(),
+            description: String::new(),
+            data: Vec::new(),
+            binary: String::new(),
+            rendering: None,
+            task_display: String::new(),
+            opcode_display: String::new(),
+            keyword_names: Vec::new(),
+            raw_xml: String::new(),
+        }
+    }
+}
+
+impl EventRecord {
+    /// Looks up a named EventData value
+    pub fn data_value(&self, name: &str) -> Option<&str> {
+        self.data.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    /// "Audit Success" or "Audit Failure" from the Keywords mask, None for events that aren't audits
+    pub fn audit_result(&self) -> Option<&'static str> {
+        audit_level(&self.keywords)
+    }
+
+    /// Task display name: the rendered one when available, otherwise looked up
+    pub fn task_label(&self) -> String {
+        if !self.task_display.is_empty() {
+            return self.task_display.clone();
+        }
+        match &self.rendering {
+            Some(r) if !r.task.is_empty() => r.task.clone(),
+            _ => task_name(&self.source, self.task),
+        }
+    }
+
+    /// Opcode display name: the rendered one when available, otherwise the standard name
+    pub fn opcode_label(&self) -> String {
+        if !self.opcode_display.is_empty() {
+            return self.opcode_display.clone();
+        }
+        match &self.rendering {
+            Some(r) if !r.opcode.is_empty() => r.opcode.clone(),
+            _ => opcode_name(self.opcode),
+        }
+    }
+
+    /// Stores the task, opcode and keyword display names on the record; parse_event
+    /// calls this so each name is looked up once per event rather than per frame
+    pub fn resolve_names(&mut self) {
+        // Cleared first so the labels are worked out afresh rather than read back
+        self.task_display = String::new();
+        self.opcode_display = String::new();
+        self.task_display = self.task_label();
+        self.opcode_display = self.opcode_label();
+        self.keyword_names = match &self.rendering {
+            Some(r) if !r.keywords.is_empty() => r.keywords.clone(),
+            _ => standard_keyword_names(&self.keywords),
+        };
+    }
+
+    /// The `<Binary>` payload decoded to bytes; None when absent or not valid hex
+    pub fn binary_bytes(&self) -> Option<Vec<u8>> {
+        let hex = self.binary.as_bytes();
+        if hex.is_empty() || !hex.len().is_multiple_of(2) {
+            return None;
+        }
+        hex.chunks(2)
+            .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+            .collect()
+    }
+
+    /// Whether `other` is the same underlying event, by channel and RecordID
+    /// when both have one, otherwise by identical timestamp and text
+    pub fn same_event(&self, other: &EventRecord) -> bool {
+        if self.record_id != 0 && other.record_id != 0 {
+            self.record_id == other.record_id && self.log_name == other.log_name
+        } else {
+            self.time_created == other.time_created
+                && self.description == other.description
+                && self.raw_xml == other.raw_xml
+        }
+    }
+
+    /// Decoded LogonType for logon success/failure events, e.g. "RemoteInteractive (10)"
+    pub fn logon_type(&self) -> Option<String> {
+        if !matches!(self.event_id, 4624 | 4625) {
+            return None;
+        }
+        let raw = self.data_value("LogonType")?;
+        Some(match logon_type_name(raw) {
+            Some(name) => format!("{} ({})", name, raw),
+            None => raw.to_string(),
+        })
+    }
+}
+
+/// Provider task names looked up so far, including misses, keyed by (provider, task)
+type TaskNameCache = HashMap<(String, u16), Option<String>>;
+static TASK_NAMES: OnceLock<Mutex<TaskNameCache>> = OnceLock::new();
+
+/// The provider's own display name for `task`, from its registered metadata.
+/// Each (provider, task) pair is looked up once.
+pub fn rendered_task_name(source: &str, task: u16) -> Option<String> {
+    if source.is_empty() {
+        return None;
+    }
+    let cache = TASK_NAMES.get_or_init(Default::default);
+    let key = (source.to_string(), task);
+    if let Some(name) = cache.lock().ok()?.get(&key) {
+        return name.clone();
+    }
+    #[cfg(target_os = "windows")]
+    let name = provider_task_name(source, task);
+    #[cfg(not(target_os = "windows"))]
+    let name = None;
+    cache.lock().ok()?.insert(key, name.clone());
+    name
+}
+
+/// Reads the task's message from the provider metadata via EvtFormatMessage
+#[cfg(target_os = "windows")]
+fn provider_task_name(provider: &str, task: u16) -> Option<String> {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::System::EventLog::{
+        EvtClose, EvtFormatMessage, EvtFormatMessageId, EvtGetObjectArrayProperty, EvtGetObjectArraySize,
+        EvtGetPublisherMetadataProperty, EvtOpenPublisherMetadata, EvtPublisherMetadataTaskMessageID,
+        EvtPublisherMetadataTaskValue, EvtPublisherMetadataTasks, EVT_HANDLE, EVT_VARIANT,
+    };
+
+    let variant_size = std::mem::size_of::<EVT_VARIANT>() as u32;
+    let mut used = 0u32;
+    // SAFETY: every handle opened here is closed before returning; the variants are
+    // plain-data out-parameters sized by variant_size, and the message buffer's
+    // length is passed with it.
+    unsafe {
+        let metadata = EvtOpenPublisherMetadata(EVT_HANDLE::default(), &HSTRING::from(provider), PCWSTR::null(), 0, 0).ok()?;
+        let mut tasks = EVT_VARIANT::default();
+        if !EvtGetPublisherMetadataProperty(metadata, EvtPublisherMetadataTasks, 0, variant_size, Some(&mut tasks), &mut used).as_bool() {
+            EvtClose(metadata);
+            return None;
+        }
+        let array = tasks.Anonymous.EvtHandleVal.0;
+        let mut count = 0u32;
+        let mut message_id = None;
+        if EvtGetObjectArraySize(array, &mut count).as_bool() {
+            for i in 0..count {
+                let mut value = EVT_VARIANT::default();
+                let read = EvtGetObjectArrayProperty(array, EvtPublisherMetadataTaskValue.0 as u32, i, 0, variant_size, Some(&mut value), &mut used);
+                if !read.as_bool() || value.Anonymous.UInt32Val != u32::from(task) {
+                    continue;
+                }
+                let mut message = EVT_VARIANT::default();
+                let read = EvtGetObjectArrayProperty(array, EvtPublisherMetadataTaskMessageID.0 as u32, i, 0, variant_size, Some(&mut message), &mut used);
+                // u32::MAX means the task has no message string
+                if read.as_bool() && message.Anonymous.UInt32Val != u32::MAX {
+                    message_id = Some(message.Anonymous.UInt32Val);
+                }
+                break;
+            }
+        }
+        EvtClose(EVT_HANDLE(array));
+        let name = message_id.and_then(|id| {
+            let _ = EvtFormatMessage(metadata, EVT_HANDLE::default(), id, None, EvtFormatMessageId.0, None, &mut used);
+            if used == 0 {
+                return None;
+            }
+            let mut buf = vec![0u16; used as usize];
+            if !EvtFormatMessage(metadata, EVT_HANDLE::default(), id, None, EvtFormatMessageId.0, Some(&mut buf), &mut used).as_bool() {
+                return None;
+            }
+            let text = String::from_utf16_lossy(&buf[..used as usize]);
+            Some(text.trim_end_matches('\0').trim().to_string()).filter(|t| !t.is_empty())
+        });
+        EvtClose(metadata);
+        name
+    }
+}
+
+/// Friendly name for the standard winmeta opcodes, falling back to the number
+pub fn opcode_name(opcode: u8) -> String {
+    match opcode {
+        0 => "Info".into(),
+        1 => "Start".into(),
+        2 => "Stop".into(),
+        3 => "DC Start".into(),
+        4 => "DC Stop".into(),
+        5 => "Extension".into(),
+        6 => "Reply".into(),
+        7 => "Resume".into(),
+        8 => "Suspend".into(),
+        9 => "Send".into(),
+        240 => "Receive".into(),
+        _ => opcode.to_string(),
+    }
+}
+
+/// Account names for SIDs that are the same on every Windows machine
+pub fn well_known_sid(sid: &str) -> Option<&'static str> {
+    Some(match sid {
+        "S-1-0-0" => "NULL SID",
+        "S-1-1-0" => "Everyone",
+        "S-1-5-2" => "NT AUTHORITY\\NETWORK",
+        "S-1-5-4" => "NT AUTHORITY\\INTERACTIVE",
+        "S-1-5-6" => "NT AUTHORITY\\SERVICE",
+        "S-1-5-7" => "NT AUTHORITY\\ANONYMOUS LOGON",
+        "S-1-5-11" => "NT AUTHORITY\\Authenticated Users",
+        "S-1-5-18" => "NT AUTHORITY\\SYSTEM (LocalSystem)",
+        "S-1-5-19" => "NT AUTHORITY\\LOCAL SERVICE",
+        "S-1-5-20" => "NT AUTHORITY\\NETWORK SERVICE",
+        "S-1-5-32-544" => "BUILTIN\\Administrators",
+        "S-1-5-32-545" => "BUILTIN\\Users",
+        "S-1-5-32-546" => "BUILTIN\\Guests",
+        "S-1-5-32-555" => "BUILTIN\\Remote Desktop Users",
+        _ => return None,
+    })
+}
+
+/// Resolves a SID string such as "S-1-5-21-..." to DOMAIN\user.
+/// Well-known SIDs come from a built-in table; others need LookupAccountSidW.
+pub fn resolve_sid(sid: &str) -> Option<String> {
+    if let Some(name) = well_known_sid(sid) {
+        return Some(name.to_string());
+    }
+    #[cfg(target_os = "windows")]
+    {
+        lookup_account_sid(sid)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        None
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn lookup_account_sid(sid: &str) -> Option<String> {
+    use windows::core::{HSTRING, PCWSTR, PWSTR};
+    use windows::Win32::Foundation::{HLOCAL, PSID};
+    use windows::Win32::Security::Authorization::ConvertStringSidToSidW;
+    use windows::Win32::Security::{LookupAccountSidW, SID_NAME_USE};
+    use windows::Win32::System::Memory::LocalFree;
+
+    let mut psid = PSID::default();
+    let mut name = [0u16; 256];
+    let mut domain = [0u16; 256];
+    let mut name_len = name.len() as u32;
+    let mut domain_len = domain.len() as u32;
+    let mut sid_use = SID_NAME_USE::default();
+    // SAFETY: psid is allocated by ConvertStringSidToSidW and freed with LocalFree below;
+    // the name buffers outlive the call and their lengths are passed alongside them.
+    let found = unsafe {
+        if !ConvertStringSidToSidW(&HSTRING::from(sid), &mut psid).as_bool() {
+            return None;
+        }
+        let found = LookupAccountSidW(
+            PCWSTR::null(),
+            psid,
+            PWSTR(name.as_mut_ptr()),
+            &mut name_len,
+            PWSTR(domain.as_mut_ptr()),
+            &mut domain_len,
+            &mut sid_use,
+        ).as_bool();
+        let _ = LocalFree(HLOCAL(psid.0 as isize));
+        found
+    };
+    if !found {
+        return None;
+    }
+    let name = String::from_utf16_lossy(&name[..name_len as usize]);
+    let domain = String::from_utf16_lossy(&domain[..domain_len as usize]);
+    Some(if domain.is_empty() { name } else { format!("{}\\{}", domain, name) })
+}
+
+/// Meaning of a Security-log LogonType value
+pub fn logon_type_name(logon_type: &str) -> Option<&'static str> {
+    Some(match logon_type.trim() {
+        "0" => "System",
+        "2" => "Interactive",
+        "3" => "Network",
+        "4" => "Batch",
+        "5" => "Service",
+        "7" => "Unlock",
+        "8" => "NetworkCleartext",
+        "9" => "NewCredentials",
+        "10" => "RemoteInteractive",
+        "11" => "CachedInteractive",
+        "12" => "CachedRemoteInteractive",
+        "13" => "CachedUnlock",
+        _ => return None,
+    })
+}
+
+/// Task Category as the provider defines it (what Event Viewer shows), then the
+/// built-in names for well-known providers, falling back to the number
+pub fn task_name(source: &str, task: u16) -> String {
+    if task == 0 {
+        return "None".into();
+    }
+    if let Some(name) = rendered_task_name(source, task) {
+        return name;
+    }
+    let name = match (source, task) {
+        ("Microsoft-Windows-Security-Auditing", id) => match id {
+            12288 => "Security State Change",
+            12289 => "Security System Extension",
+            12290 => "System Integrity",
+            12291 => "IPsec Driver",
+            12292 => "Other System Events",
+            12544 => "Logon",
+            12545 => "Logoff",
+            12546 => "Account Lockout",
+            12547 => "IPsec Main Mode",
+            12548 => "Special Logon",
+            12549 => "IPsec Extended Mode",
+            12550 => "IPsec Quick Mode",
+            12551 => "Other Logon/Logoff Events",
+            12552 => "Network Policy Server",
+            12553 => "User / Device Claims",
+            12554 => "Group Membership",
+            12800 => "File System",
+            12801 => "Registry",
+            12802 => "Kernel Object",
+            12803 => "SAM",
+            12804 => "Other Object Access Events",
+            12805 => "Certification Services",
+            12806 => "Application Generated",
+            12807 => "Handle Manipulation",
+            12808 => "File Share",
+            12809 => "Filtering Platform Packet Drop",
+            12810 => "Filtering Platform Connection",
+            12811 => "Detailed File Share",
+            12812 => "Removable Storage",
+            13056 => "Sensitive Privilege Use",
+            13057 => "Non Sensitive Privilege Use",
+            13058 => "Other Privilege Use Events",
+            13312 => "Process Creation",
+            13313 => "Process Termination",
+            13314 => "DPAPI Activity",
+            13315 => "RPC Events",
+            13316 => "Plug and Play Events",
+            13568 => "Audit Policy Change",
+            13569 => "Authentication Policy Change",
+            13570 => "Authorization Policy Change",
+            13571 => "MPSSVC Rule-Level Policy Change",
+            13572 => "Filtering Platform Policy Change",
+            13573 => "Other Policy Change Events",
+            13824 => "User Account Management",
+            13825 => "Computer Account Management",
+            13826 => "Security Group Management",
+            13827 => "Distribution Group Management",
+            13828 => "Application Group Management",
+            13829 => "Other Account Management Events",
+            14080 => "Directory Service Access",
+            14081 => "Directory Service Changes",
+            14082 => "Directory Service Replication",
+            14083 => "Detailed Directory Service Replication",
+            14336 => "Credential Validation",
+            14337 => "Kerberos Service Ticket Operations",
+            14338 => "Other Account Logon Events",
+            14339 => "Kerberos Authentication Service",
+            _ => return task.to_string(),
+        },
+        _ => return task.to_string(),
+    };
+    name.to_string()
+}
+
+/// Level names offered by the level filter, including the audit levels
+/// derived from Keywords for Security events
+pub const LEVELS: [&str; 7] = ["Critical", "Error", "Warning", "Audit Failure", "Information", "Audit Success", "Verbose"];
+
+/// Keywords bits marking an audit event's outcome
+pub const KEYWORD_AUDIT_FAILURE: u64 = 0x0010_0000_0000_0000;
+pub const KEYWORD_AUDIT_SUCCESS: u64 = 0x0020_0000_0000_0000;
+
+/// Names Event Viewer shows for the standard Keywords bits
+const STANDARD_KEYWORDS: [(u64, &str); 8] = [
+    (0x0001_0000_0000_0000, "Response Time"),
+    (0x0002_0000_0000_0000, "WDI Context"),
+    (0x0004_0000_0000_0000, "WDI Diag"),
+    (0x0008_0000_0000_0000, "SQM"),
+    (KEYWORD_AUDIT_FAILURE, "Audit Failure"),
+    (KEYWORD_AUDIT_SUCCESS, "Audit Success"),
+    (0x0040_0000_0000_0000, "Correlation Hint"),
+    (0x0080_0000_0000_0000, "Classic"),
+];
+
+/// Names of the standard bits set in a Keywords mask such as "0x8020000000000000";
+/// provider-specific bits have no name without the provider's metadata
+pub fn standard_keyword_names(keywords: &str) -> Vec<String> {
+    let Ok(mask) = u64::from_str_radix(keywords.trim().trim_start_matches("0x"), 16) else {
+        return Vec::new();
+    };
+    STANDARD_KEYWORDS.iter().filter(|(bit, _)| mask & bit != 0).map(|(_, name)| name.to_string()).collect()
+}
+
+/// "Audit Success" or "Audit Failure" when the Keywords mask carries an audit bit.
+/// Security events are logged at level 0, so this is the only usable level they have.
+pub fn audit_level(keywords: &str) -> Option<&'static str> {
+    let mask = u64::from_str_radix(keywords.trim().trim_start_matches("0x"), 16).ok()?;
+    if mask & KEYWORD_AUDIT_FAILURE != 0 {
+        Some("Audit Failure")
+    } else if mask & KEYWORD_AUDIT_SUCCESS != 0 {
+        Some("Audit Success")
+    } else {
+        None
+    }
+}
+
+/// Orders level names by severity so Critical sorts above Verbose
+pub fn level_severity(level: &str) -> u8 {
+    match level {
+        "Critical" => 5,
+        "Error" => 4,
+        "Warning" | "Audit Failure" => 3,
+        "Information" | "Info" | "Audit Success" => 2,
+        "Verbose" => 1,
+        _ => 0,
+    }
+}
+
 pub fn list_event_logs() -> Vec<String> {
     #[cfg(target_os = "windows")]
     {
@@ -34,167 +521,1039 @@ pub fn list_event_logs() -> Vec<String> {
     }
 }
 
-pub fn query_events(log: &str, max_records: u32) -> Vec<EventRecord> {
+/// Whether queries ask wevtutil for /f:RenderedXml rather than plain XML
+static RENDERED_XML: AtomicBool = AtomicBool::new(true);
+
+/// Chooses the wevtutil output format for queries made from now on
+pub fn set_rendered_xml(enabled: bool) {
+    RENDERED_XML.store(enabled, Ordering::Relaxed);
+}
+
+/// Seconds a query may run before wevtutil is killed; 0 waits indefinitely
+static QUERY_TIMEOUT_SECS: AtomicU32 = AtomicU32::new(120);
+
+/// Sets how long queries made from now on may run, in seconds (0 for no limit)
+pub fn set_query_timeout(secs: u32) {
+    QUERY_TIMEOUT_SECS.store(secs, Ordering::Relaxed);
+}
+
+/// Reads the newest `max_records` events from `log`, newest first.
+/// Errors carry a message suitable for showing to the user.
+pub fn query_events(log: &str, max_records: u32) -> Result<Vec<EventRecord>, String> {
+    query_events_progress(log, max_records, &mut |_| {})
+}
+
+/// query_events that also hands each batch of events to `progress` as wevtutil prints
+/// them, so a large fetch can be shown while it runs. When rendered XML fails part way
+/// the plain XML retry reports its events again from the start.
+pub fn query_events_progress(log: &str, max_records: u32, progress: &mut dyn FnMut(&[EventRecord])) -> Result<Vec<EventRecord>, String> {
     #[cfg(target_os = "windows")]
     {
-        let args = ["qe", log, "/f:xml", &format!("/c:{}", max_records), "/rd:true"];
-        let output = Command::new("wevtutil")
-            .args(&args)
-            .output()
-            .unwrap_or_else(|e| {
-                eprintln!("Failed to execute wevtutil: {}", e);
-                std::process::exit(1);
-            });
-        if !output.status.success() {
-            eprintln!(
-                "wevtutil qe error: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-            return Vec::new();
-        }
-        let xml = String::from_utf8_lossy(&output.stdout);
-        let mut events = Vec::new();
-        for raw in xml.split("</Event>") {
-            let raw = raw.trim();
-            if raw.is_empty() {
-                continue;
-            }
-            let raw_event = format!("{}{}", raw, "</Event>");
-            if let Some(ev) = parse_event(&raw_event) {
-                events.push(ev);
+        let fallback = format!("Get-WinEvent -LogName {} -MaxEvents {}", ps_quote(log), max_records);
+        let count = format!("/c:{}", max_records);
+        if RENDERED_XML.load(Ordering::Relaxed) {
+            // Rendering fails outright when a provider's metadata is missing; plain XML still works.
+            // A query that timed out would only hang again.
+            match wevtutil_query(&["qe", log, "/f:RenderedXml", &count, "/rd:true"], &fallback, log, log, progress) {
+                Ok(events) => return Ok(events),
+                Err(e) if e.contains("timed out") => return Err(e),
+                Err(_) => {}
             }
         }
-        events
+        wevtutil_query(&["qe", log, "/f:xml", &count, "/rd:true"], &fallback, log, log, progress)
     }
     #[cfg(not(target_os = "windows"))]
     {
+        let _ = progress;
         // For Unix: read from /var/log/system.log (macOS) or /var/log/syslog (Linux)
         let log_path = if cfg!(target_os = "macos") {
             "/var/log/system.log"
         } else {
             "/var/log/syslog"
         };
-        let file = File::open(log_path);
-        if file.is_err() {
-            eprintln!("Failed to open system log: {}", log_path);
-            return Vec::new();
-        }
-        let reader = BufReader::new(file.unwrap());
-        let lines: Vec<_> = reader.lines().filter_map(Result::ok).collect();
+        let file = File::open(log_path)
+            .map_err(|e| format!("Failed to open system log {}: {}", log_path, e))?;
+        let reader = BufReader::new(file);
+        let lines: Vec<_> = reader.lines().map_while(Result::ok).collect();
         let mut events = Vec::new();
         for line in lines.iter().rev().take(max_records as usize) {
             let record = EventRecord {
                 log_name: log.to_string(),
                 time_created: Local::now(), // Could parse from line if format known
-                event_id: 0,
-                level: String::new(),
-                source: String::new(),
-                user: String::new(),
-                computer: String::new(),
                 description: line.clone(),
                 raw_xml: line.clone(),
+                ..Default::default()
             };
             events.push(record);
         }
+        Ok(events)
+    }
+}
+
+/// query_events for each of `logs` in turn, merged newest first. wevtutil takes one
+/// channel per query, so a comma-joined list can't be passed instead. A channel that
+/// fails adds its error to the second value without stopping the others.
+pub fn query_channels(logs: &[String], max_records: u32) -> (Vec<EventRecord>, Vec<String>) {
+    let mut events = Vec::new();
+    let mut errors = Vec::new();
+    for log in logs {
+        match query_events(log, max_records) {
+            Ok(found) => events.extend(found),
+            Err(e) => errors.push(e),
+        }
+    }
+    events.sort_by_key(|e| std::cmp::Reverse(e.time_created));
+    (events, errors)
+}
+
+/// Re-reads one event by its EventRecordID with full rendering, for when the bulk
+/// query came back without a rendered message or with fields missing
+pub fn query_record(channel: &str, record_id: u64) -> Result<EventRecord, String> {
+    #[cfg(target_os = "windows")]
+    {
+        let xpath = format!("*[System[(EventRecordID={})]]", record_id);
+        let fallback = format!("Get-WinEvent -LogName {} -FilterXPath {} -MaxEvents 1", ps_quote(channel), ps_quote(&xpath));
+        let query = format!("/q:{}", xpath);
+        // As in query_events: a provider without metadata still gives plain XML
+        let events = match wevtutil_query(&["qe", channel, &query, "/f:RenderedXml", "/c:1"], &fallback, channel, channel, &mut |_| {}) {
+            Ok(events) => events,
+            Err(e) if e.contains("timed out") => return Err(e),
+            Err(_) => wevtutil_query(&["qe", channel, &query, "/f:xml", "/c:1"], &fallback, channel, channel, &mut |_| {})?,
+        };
+        events
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("Event {} is no longer in {}", record_id, channel))
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err(format!("Cannot fetch event {} from {}: this needs wevtutil, which is Windows-only", record_id, channel))
+    }
+}
+
+/// Runs a saved structured query (a `<QueryList>` XML file) with `wevtutil qe <file> /sq:true`,
+/// newest first, so teams can share canned queries. Each batch of events is also handed
+/// to `progress` as it arrives, as in query_events_progress.
+pub fn query_structured(path: &Path, max_records: u32, progress: &mut dyn FnMut(&[EventRecord])) -> Result<Vec<EventRecord>, String> {
+    #[cfg(target_os = "windows")]
+    {
+        let file = path.to_string_lossy();
+        let fallback = format!("Get-WinEvent -FilterXml ([xml](Get-Content -Raw {})) -MaxEvents {}", ps_quote(&file), max_records);
+        let count = format!("/c:{}", max_records);
+        if RENDERED_XML.load(Ordering::Relaxed) {
+            match wevtutil_query(&["qe", &file, "/sq:true", "/f:RenderedXml", &count, "/rd:true"], &fallback, &file, "", progress) {
+                Ok(events) => return Ok(events),
+                Err(e) if e.contains("timed out") => return Err(e),
+                Err(_) => {}
+            }
+        }
+        wevtutil_query(&["qe", &file, "/sq:true", "/f:xml", &count, "/rd:true"], &fallback, &file, "", progress)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (max_records, progress);
+        Err(format!("Cannot run {}: structured queries need wevtutil, which is Windows-only", path.display()))
+    }
+}
+
+/// Starts the built-in Event Viewer (eventvwr) with `channel` selected
+pub fn open_in_event_viewer(channel: &str) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
+        Command::new(system_tool(r"System32\eventvwr.exe", "eventvwr"))
+            .arg(format!("/c:{}", channel))
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| format!("Cannot start Event Viewer: {}", e))
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err(format!("Cannot open {}: Event Viewer is Windows-only", channel))
+    }
+}
+
+/// Settings and file state of an event log channel, from `wevtutil gl` and `gli`
+#[derive(Clone, Debug, Default)]
+pub struct ChannelInfo {
+    pub enabled: bool,
+    pub log_file: String,
+    /// Size cap in bytes
+    pub max_size: Option<u64>,
+    pub file_size: Option<u64>,
+    pub record_count: Option<u64>,
+    /// Keep events instead of overwriting the oldest when the log is full
+    pub retention: bool,
+    /// Archive the full log to a new file instead of overwriting
+    pub auto_backup: bool,
+}
+
+impl ChannelInfo {
+    /// What happens when the log reaches max_size, worded as in Event Viewer
+    pub fn retention_policy(&self) -> &'static str {
+        match (self.retention, self.auto_backup) {
+            (_, true) => "Archive the log when full",
+            (true, false) => "Do not overwrite events",
+            (false, false) => "Overwrite events as needed",
+        }
+    }
+
+    /// Whether the log file is at (or within 5% of) its size cap, so older events
+    /// are being overwritten or new ones refused
+    pub fn is_full(&self) -> bool {
+        match (self.file_size, self.max_size) {
+            (Some(size), Some(max)) if max > 0 => size >= max - max / 20,
+            _ => false,
+        }
+    }
+}
+
+/// Reads the configuration and current size of `channel`; blocks until wevtutil
+/// finishes or the query timeout passes
+pub fn channel_info(channel: &str) -> Result<ChannelInfo, String> {
+    #[cfg(target_os = "windows")]
+    {
+        let action = format!("Reading details of {}", channel);
+        let gl = wevtutil_output(&["gl", channel], &action)?;
+        let gli = wevtutil_output(&["gli", channel], &action)?;
+        Ok(parse_channel_info(&gl, &gli))
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err(format!("Channel details for {} need wevtutil, which is Windows-only", channel))
+    }
+}
+
+/// Picks the fields of ChannelInfo out of the `key: value` lines printed by
+/// `wevtutil gl` and `gli`; section headers and indentation don't matter
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_channel_info(gl: &str, gli: &str) -> ChannelInfo {
+    let mut info = ChannelInfo::default();
+    for line in gl.lines().chain(gli.lines()) {
+        let Some((key, value)) = line.trim().split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key {
+            "enabled" => info.enabled = value == "true",
+            "logFileName" => info.log_file = value.to_string(),
+            "maxSize" => info.max_size = value.parse().ok(),
+            "fileSize" => info.file_size = value.parse().ok(),
+            "numberOfLogRecords" => info.record_count = value.parse().ok(),
+            "retention" => info.retention = value == "true",
+            "autoBackup" => info.auto_backup = value == "true",
+            _ => {}
+        }
+    }
+    info
+}
+
+/// A provider's message templates keyed by (event ID, version)
+pub type MessageTemplates = HashMap<(u16, u8), String>;
+
+/// Message templates of `provider`'s events, from `wevtutil gp <provider> /ge /gm:true`;
+/// blocks until wevtutil finishes or the query timeout passes
+pub fn provider_templates(provider: &str) -> Result<MessageTemplates, String> {
+    #[cfg(target_os = "windows")]
+    {
+        let output = wevtutil_output(&["gp", provider, "/ge", "/gm:true"], &format!("Reading messages of {}", provider))?;
+        Ok(parse_provider_templates(&output))
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err(format!("Message templates of {} need wevtutil, which is Windows-only", provider))
+    }
+}
+
+/// Reads the `event:` blocks of `wevtutil gp /ge /gm:true` output. A block's message
+/// runs from its `message:` line to the end of the block, line breaks included.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_provider_templates(output: &str) -> MessageTemplates {
+    let mut templates = HashMap::new();
+    let mut current: Option<(u16, u8, Option<String>)> = None;
+    let mut finish = |block: Option<(u16, u8, Option<String>)>| {
+        if let Some((id, version, Some(message))) = block {
+            templates.insert((id, version), message.trim_end().to_string());
+        }
+    };
+    for line in output.lines() {
+        if line.trim() == "event:" {
+            finish(current.take());
+            current = Some((0, 0, None));
+            continue;
+        }
+        let Some((id, version, message)) = current.as_mut() else {
+            continue;
+        };
+        if let Some(message) = message {
+            message.push('\n');
+            message.push_str(line);
+            continue;
+        }
+        match line.trim().split_once(':') {
+            Some(("value", value)) => *id = value.trim().parse().unwrap_or(0),
+            Some(("version", value)) => *version = value.trim().parse().unwrap_or(0),
+            Some(("message", text)) => *message = Some(text.trim_start().to_string()),
+            _ => {}
+        }
+    }
+    finish(current);
+    templates
+}
+
+/// Fills a message template's `%1`, `%2`, ... inserts with `values` (1-based, any
+/// `!format!` suffix dropped) and expands the FormatMessage escapes `%t`, `%n`,
+/// `%b`, `%r` and `%%`. Inserts without a value are left as written.
+pub fn format_template(template: &str, values: &[&str]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.peek().copied() {
+            Some(d) if d.is_ascii_digit() && d != '0' => {
+                let mut number = String::new();
+                while let Some(d) = chars.peek().copied().filter(char::is_ascii_digit) {
+                    number.push(d);
+                    chars.next();
+                }
+                // A printf-style format such as !s! or !08x!; the value goes in as text either way
+                if chars.peek() == Some(&'!') {
+                    if let Some(len) = chars.clone().skip(1).position(|c| c == '!') {
+                        chars.nth(len + 1);
+                    }
+                }
+                match number.parse::<usize>().ok().and_then(|n| values.get(n - 1)) {
+                    Some(value) => out.push_str(value),
+                    None => {
+                        out.push('%');
+                        out.push_str(&number);
+                    }
+                }
+            }
+            Some(escape @ ('t' | 'n' | 'b' | 'r' | '%')) => {
+                chars.next();
+                match escape {
+                    't' => out.push('\t'),
+                    'n' => out.push('\n'),
+                    'b' => out.push(' '),
+                    'r' => {}
+                    _ => out.push('%'),
+                }
+            }
+            _ => out.push('%'),
+        }
+    }
+    out
+}
+
+/// Runs `wevtutil` with `args` and parses the XML events it prints, passing each
+/// chunk's events to `progress` as they arrive. When wevtutil can't be started at all,
+/// the `powershell` Get-WinEvent pipeline runs instead. `target` names the log or
+/// query file in error messages; `channel` fills in events that don't name theirs.
+/// The process is killed once it runs past the query timeout.
+#[cfg(target_os = "windows")]
+fn wevtutil_query(args: &[&str], powershell: &str, target: &str, channel: &str, progress: &mut dyn FnMut(&[EventRecord])) -> Result<Vec<EventRecord>, String> {
+    let spawn = |program: std::path::PathBuf, args: &[&str]| {
+        Command::new(program).args(args).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()
+    };
+    let child = match spawn(system_tool(r"System32\wevtutil.exe", "wevtutil"), args) {
+        Ok(child) => child,
+        Err(wevtutil_err) => {
+            // Newest first like /rd:true; each event printed as its XML
+            let script = format!(
+                "[Console]::OutputEncoding = [Text.Encoding]::UTF8; {} | ForEach-Object {{ $_.ToXml() }}",
+                powershell
+            );
+            let ps_args = ["-NoProfile", "-NonInteractive", "-Command", &script];
+            spawn(system_tool(r"System32\WindowsPowerShell\v1.0\powershell.exe", "powershell"), &ps_args)
+                .map_err(|ps_err| format!(
+                    "Cannot query {}: wevtutil failed to start ({}) and so did PowerShell ({})",
+                    target, wevtutil_err, ps_err
+                ))?
+        }
+    };
+    let mut stream = EventStream { channel: channel.to_string(), ..Default::default() };
+    let mut events = Vec::new();
+    read_until_deadline(child, &format!("Query of {}", target), &mut |bytes| {
+        let batch = stream.push(bytes, bytes.is_empty());
+        if !batch.is_empty() {
+            progress(&batch);
+            events.extend(batch);
+        }
+    })?;
+    Ok(events)
+}
+
+/// Passes the stdout of `child` to `on_chunk` as it arrives, ending with an empty chunk,
+/// then checks how it exited. The child is killed once it runs past the query timeout.
+/// `action` starts the error messages, e.g. "Query of Application".
+#[cfg(target_os = "windows")]
+fn read_until_deadline(mut child: std::process::Child, action: &str, on_chunk: &mut dyn FnMut(&[u8])) -> Result<(), String> {
+    let failed = |e: std::io::Error| format!("{} failed: {}", action, e);
+    let mut stdout = child.stdout.take().ok_or_else(|| format!("{} failed: no output", action))?;
+    // Read on another thread so a wevtutil that stops printing can't block past the deadline;
+    // an empty chunk marks the end of output
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let mut chunk = vec![0u8; 64 * 1024];
+        loop {
+            let read = stdout.read(&mut chunk).map(|n| chunk[..n].to_vec());
+            let done = !matches!(&read, Ok(bytes) if !bytes.is_empty());
+            if tx.send(read).is_err() || done {
+                return;
+            }
+        }
+    });
+    let timeout = QUERY_TIMEOUT_SECS.load(Ordering::Relaxed);
+    let deadline = (timeout > 0).then(|| std::time::Instant::now() + std::time::Duration::from_secs(timeout.into()));
+    loop {
+        let next = match deadline {
+            Some(deadline) => rx.recv_timeout(deadline.saturating_duration_since(std::time::Instant::now())),
+            None => rx.recv().map_err(|_| std::sync::mpsc::RecvTimeoutError::Disconnected),
+        };
+        let bytes = match next {
+            Ok(read) => read.map_err(failed)?,
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("{} timed out after {}s", action, timeout));
+            }
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => Vec::new(),
+        };
+        on_chunk(&bytes);
+        if bytes.is_empty() {
+            break;
+        }
+    }
+    let mut stderr = String::new();
+    if let Some(mut pipe) = child.stderr.take() {
+        let _ = pipe.read_to_string(&mut stderr);
+    }
+    if !child.wait().map_err(failed)?.success() {
+        return Err(format!("{} failed: {}", action, stderr.trim()));
+    }
+    Ok(())
+}
+
+/// Runs `wevtutil` with `args` under the query timeout and returns what it printed
+#[cfg(target_os = "windows")]
+fn wevtutil_output(args: &[&str], action: &str) -> Result<String, String> {
+    let child = Command::new(system_tool(r"System32\wevtutil.exe", "wevtutil"))
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("{} failed: wevtutil failed to start ({})", action, e))?;
+    let mut output = Vec::new();
+    read_until_deadline(child, action, &mut |bytes| output.extend_from_slice(bytes))?;
+    Ok(decode_output(&output))
+}
+
+/// Splits wevtutil output into events as it arrives. The encoding is picked from the
+/// first bytes the way decode_output picks it for a whole buffer.
+#[derive(Default)]
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+struct EventStream {
+    decoder: Option<encoding_rs::Decoder>,
+    /// Bytes held until there are enough to tell the encoding
+    head: Vec<u8>,
+    /// Decoded text after the last complete event
+    text: String,
+    /// Channel queried, for events without a <Channel> element
+    channel: String,
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+impl EventStream {
+    /// Adds the next chunk of output (`last` once it has ended) and returns the events it completed
+    fn push(&mut self, bytes: &[u8], last: bool) -> Vec<EventRecord> {
+        let bytes = match &self.decoder {
+            Some(_) => bytes,
+            None => {
+                self.head.extend_from_slice(bytes);
+                if self.head.len() < 4 && !last {
+                    return Vec::new();
+                }
+                let head = &self.head;
+                let encoding = if head.len() >= 2 && head[1] == 0 && head[0] != 0 {
+                    encoding_rs::UTF_16LE
+                } else {
+                    match std::str::from_utf8(head) {
+                        // A multi-byte character cut off by the chunk boundary is still UTF-8
+                        Err(e) if e.error_len().is_some() => encoding_rs::WINDOWS_1252,
+                        _ => encoding_rs::UTF_8,
+                    }
+                };
+                // new_decoder switches to the encoding of a BOM when there is one
+                self.decoder = Some(encoding.new_decoder());
+                &self.head[..]
+            }
+        };
+        let decoder = self.decoder.as_mut().expect("decoder chosen above");
+        if let Some(needed) = decoder.max_utf8_buffer_length(bytes.len()) {
+            self.text.reserve(needed);
+        }
+        let _ = decoder.decode_to_string(bytes, &mut self.text, last);
+        self.head = Vec::new();
+        let mut events = Vec::new();
+        let mut consumed = 0;
+        while let Some(end) = self.text[consumed..].find("</Event>") {
+            let end = consumed + end + "</Event>".len();
+            let raw = self.text[consumed..end].trim();
+            if raw != "</Event>" {
+                if let Some(ev) = parse_event(raw, &self.channel) {
+                    events.push(ev);
+                }
+            }
+            consumed = end;
+        }
+        self.text.drain(..consumed);
         events
     }
 }
 
-/// Parses an individual Event XML into EventRecord
-fn parse_event(xml: &str) -> Option<EventRecord> {
+/// Full path of a Windows tool under %SystemRoot%, so a missing or odd PATH doesn't
+/// matter; the bare name (resolved through PATH) when that file isn't there
+#[cfg(target_os = "windows")]
+fn system_tool(relative: &str, name: &str) -> std::path::PathBuf {
+    std::env::var_os("SystemRoot")
+        .map(|root| Path::new(&root).join(relative))
+        .filter(|path| path.is_file())
+        .unwrap_or_else(|| name.into())
+}
+
+/// Single-quotes a PowerShell string argument
+#[cfg(target_os = "windows")]
+fn ps_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// Decodes wevtutil's stdout, which depending on locale and redirection is UTF-8,
+/// UTF-16 (with or without a BOM) or the ANSI code page
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn decode_output(bytes: &[u8]) -> String {
+    if let Some((encoding, bom_len)) = encoding_rs::Encoding::for_bom(bytes) {
+        return encoding.decode_without_bom_handling(&bytes[bom_len..]).0.into_owned();
+    }
+    // '<' followed by a NUL is UTF-16LE markup without a BOM
+    if bytes.len() >= 2 && bytes[1] == 0 && bytes[0] != 0 {
+        return encoding_rs::UTF_16LE.decode_without_bom_handling(bytes).0.into_owned();
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => encoding_rs::WINDOWS_1252.decode_without_bom_handling(bytes).0.into_owned(),
+    }
+}
+
+/// Reads every record of an exported .evtx file, oldest first. Records that fail
+/// to decode are skipped and counted in the second value.
+pub fn read_evtx(path: &Path) -> Result<(Vec<EventRecord>, usize), String> {
+    read_evtx_progress(path, &mut |_| {})
+}
+
+/// read_evtx that also hands each batch of parsed events to `progress`, so a large
+/// file can be shown while it is read
+pub fn read_evtx_progress(path: &Path, progress: &mut dyn FnMut(&[EventRecord])) -> Result<(Vec<EventRecord>, usize), String> {
+    const BATCH: usize = 1000;
+    let mut parser = evtx::EvtxParser::from_path(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let fallback_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let mut events = Vec::new();
+    let mut skipped = 0;
+    let mut reported = 0;
+    for record in parser.records() {
+        match record.ok().and_then(|r| parse_event(&r.data, &fallback_name)) {
+            Some(ev) => events.push(ev),
+            None => skipped += 1,
+        }
+        if events.len() - reported >= BATCH {
+            progress(&events[reported..]);
+            reported = events.len();
+        }
+    }
+    if reported < events.len() {
+        progress(&events[reported..]);
+    }
+    Ok((events, skipped))
+}
+
+/// Parses every <Event> element in an exported XML document, such as `wevtutil qe /f:xml`
+/// output or Event Viewer's "Save as XML", with parse_event. Returns the events read with
+/// the XML error that cut the document short, if any.
+pub fn parse_events_xml(text: &str, channel: &str) -> (Vec<EventRecord>, Option<String>) {
+    let mut reader = Reader::from_str(text);
+    let mut events = Vec::new();
+    // Byte offset of the open <Event> and how deep inside it the reader is
+    let mut open: Option<(usize, usize)> = None;
+    loop {
+        let start = reader.buffer_position();
+        match reader.read_event() {
+            Ok(XmlEvent::Start(ref e)) => match open.as_mut() {
+                Some((_, depth)) => *depth += 1,
+                None if e.local_name().as_ref() == b"Event" => open = Some((start, 0)),
+                None => {}
+            },
+            Ok(XmlEvent::End(_)) => match open {
+                Some((_, ref mut depth)) if *depth > 0 => *depth -= 1,
+                Some((from, _)) => {
+                    events.extend(parse_event(&text[from..reader.buffer_position()], channel));
+                    open = None;
+                }
+                None => {}
+            },
+            Ok(XmlEvent::Eof) if open.is_some() => return (events, Some("the last <Event> is cut off".to_string())),
+            Ok(XmlEvent::Eof) => return (events, None),
+            Err(e) => return (events, Some(e.to_string())),
+            _ => {}
+        }
+    }
+}
+
+/// Parses an individual Event XML into EventRecord. `channel` becomes the log_name
+/// when the XML has no <Channel> element.
+fn parse_event(xml: &str, channel: &str) -> Option<EventRecord> {
     let mut reader = Reader::from_str(xml);
     reader.trim_text(true);
     let mut buf = Vec::new();
     let mut record = EventRecord {
-        log_name: String::new(),
-        time_created: Local::now(),
-        event_id: 0,
-        level: String::new(),
-        source: String::new(),
-        user: String::new(),
-        computer: String::new(),
-        description: String::new(),
         raw_xml: xml.to_string(),
+        ..Default::default()
     };
     loop {
         match reader.read_event_into(&mut buf) {
+            Ok(XmlEvent::Empty(ref e)) if e.name().as_ref() == b"Data" => {
+                // A self-closed <Data Name='x'/> still names a field, just with no value
+                if let Some(name) = data_name(e) {
+                    record.data.push((name, String::new()));
+                }
+            }
+            Ok(XmlEvent::Empty(ref e)) => read_system_attributes(e, &mut record),
             Ok(XmlEvent::Start(ref e)) => match e.name().as_ref() {
-                b"Provider" => {
-                    for attr in e.attributes().with_checks(false) {
-                        if let Ok(attr) = attr {
-                            if attr.key.as_ref() == b"Name" {
-                                record.source = attr.unescape_value().unwrap_or_default().to_string();
-                            }
-                        }
-                    }
+                b"EventID" => record.event_id = element_text(&mut reader).parse().unwrap_or(0),
+                b"Level" => {
+                    let lvl = element_text(&mut reader);
+                    record.level_num = lvl.parse().unwrap_or(0);
+                    record.level = match lvl.as_str() {
+                        "1" => "Critical".into(),
+                        "2" => "Error".into(),
+                        "3" => "Warning".into(),
+                        "4" => "Information".into(),
+                        "5" => "Verbose".into(),
+                        _ => lvl,
+                    };
                 }
-                b"EventID" => {
-                    if let Ok(XmlEvent::Text(e)) = reader.read_event_into(&mut buf) {
-                        let text = e.unescape().unwrap_or_default().to_string();
-                        record.event_id = text.parse().unwrap_or(0);
-                    }
+                b"Opcode" => record.opcode = element_text(&mut reader).parse().unwrap_or(0),
+                b"EventRecordID" => record.record_id = element_text(&mut reader).parse().unwrap_or(0),
+                b"Task" => record.task = element_text(&mut reader).parse().unwrap_or(0),
+                b"Version" => record.version = element_text(&mut reader).parse().unwrap_or(0),
+                b"Keywords" => record.keywords = element_text(&mut reader),
+                b"Computer" => record.computer = element_text(&mut reader),
+                b"Data" => {
+                    let name = data_name(e);
+                    let data = element_text(&mut reader);
+                    push_data(&mut record, name, data);
                 }
-                b"Level" => {
-                    if let Ok(XmlEvent::Text(e)) = reader.read_event_into(&mut buf) {
-                        let lvl = e.unescape().unwrap_or_default().to_string();
-                        record.level = match lvl.as_str() {
-                            "1" => "Critical".into(),
-                            "2" => "Error".into(),
-                            "3" => "Warning".into(),
-                            "4" => "Information".into(),
-                            "5" => "Verbose".into(),
-                            _ => lvl,
-                        };
-                    }
+                b"UserData" => read_user_data(&mut reader, &mut record),
+                b"Binary" => record.binary = element_text(&mut reader).trim().to_string(),
+                // Read as a block: its Level, Task and Keywords are names, not the System values
+                b"RenderingInfo" => record.rendering = Some(read_rendering_info(&mut reader)),
+                b"Channel" => record.log_name = element_text(&mut reader),
+                _ => read_system_attributes(e, &mut record),
+            },
+            Ok(XmlEvent::Eof) => break,
+            Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    if let Some(level) = audit_level(&record.keywords) {
+        record.level = level.to_string();
+    }
+    // The provider's formatted message reads like Event Viewer; joined Data is the fallback
+    if let Some(message) = record.rendering.as_ref().map(|r| r.message.trim()).filter(|m| !m.is_empty()) {
+        record.description = message.to_string();
+    }
+    if record.log_name.is_empty() {
+        record.log_name = channel.to_string();
+    }
+    record.resolve_names();
+    Some(record)
+}
+
+/// Text of the element whose start tag was just read, consuming through its end tag.
+/// An empty element yields "", so the next element's text is never taken by mistake.
+fn element_text(reader: &mut Reader<&[u8]>) -> String {
+    let mut buf = Vec::new();
+    let mut text = String::new();
+    let mut depth = 0usize;
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(XmlEvent::Text(t)) => text.push_str(&t.unescape().unwrap_or_default()),
+            Ok(XmlEvent::CData(c)) => text.push_str(&String::from_utf8_lossy(&c)),
+            Ok(XmlEvent::Start(_)) => depth += 1,
+            Ok(XmlEvent::End(_)) if depth == 0 => break,
+            Ok(XmlEvent::End(_)) => depth -= 1,
+            Ok(XmlEvent::Eof) | Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    text
+}
+
+/// How Data values are joined into a description: the text between values, and
+/// whether named values are written as Name=Value
+struct DescriptionFormat {
+    separator: String,
+    with_names: bool,
+}
+
+static DESCRIPTION_FORMAT: OnceLock<RwLock<DescriptionFormat>> = OnceLock::new();
+
+fn description_format() -> &'static RwLock<DescriptionFormat> {
+    DESCRIPTION_FORMAT.get_or_init(|| RwLock::new(DescriptionFormat { separator: "; ".to_string(), with_names: false }))
+}
+
+/// Sets how descriptions are built for events parsed from now on
+pub fn set_description_format(separator: &str, with_names: bool) {
+    if let Ok(mut format) = description_format().write() {
+        *format = DescriptionFormat { separator: separator.to_string(), with_names };
+    }
+}
+
+/// Adds a payload value to the description and, when it has a name, to the structured data
+pub fn push_data(record: &mut EventRecord, name: Option<String>, value: String) {
+    if !value.is_empty() {
+        let format = description_format().read();
+        let (separator, with_names) = match &format {
+            Ok(f) => (f.separator.as_str(), f.with_names),
+            Err(_) => ("; ", false),
+        };
+        if !record.description.is_empty() {
+            record.description.push_str(separator);
+        }
+        if let (true, Some(name)) = (with_names, &name) {
+            record.description.push_str(&format!("{}={}", name, value));
+        } else {
+            record.description.push_str(&value);
+        }
+    }
+    if let Some(name) = name {
+        record.data.push((name, value));
+    }
+}
+
+/// Reads a `<UserData>` payload, whose layout is provider-defined: every element
+/// without children becomes a field named after the element, at any depth
+fn read_user_data(reader: &mut Reader<&[u8]>, record: &mut EventRecord) {
+    let mut buf = Vec::new();
+    // Open elements as (local name, text so far, has child elements)
+    let mut stack: Vec<(String, String, bool)> = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(XmlEvent::Start(e)) => {
+                if let Some(parent) = stack.last_mut() {
+                    parent.2 = true;
                 }
-                b"TimeCreated" => {
-                    for attr in e.attributes().with_checks(false) {
-                        if let Ok(attr) = attr {
-                            if attr.key.as_ref() == b"SystemTime" {
-                                if let Ok(ts) = attr.unescape_value() {
-                                    if let Ok(dt) = DateTime::parse_from_rfc3339(&ts) {
-                                        record.time_created = dt.with_timezone(&Local);
-                                    }
-                                }
-                            }
-                        }
-                    }
+                stack.push((String::from_utf8_lossy(e.local_name().as_ref()).to_string(), String::new(), false));
+            }
+            Ok(XmlEvent::Empty(e)) => {
+                if let Some(parent) = stack.last_mut() {
+                    parent.2 = true;
                 }
-                b"Computer" => {
-                    if let Ok(XmlEvent::Text(e)) = reader.read_event_into(&mut buf) {
-                        record.computer = e.unescape().unwrap_or_default().to_string();
-                    }
+                push_data(record, Some(String::from_utf8_lossy(e.local_name().as_ref()).to_string()), String::new());
+            }
+            Ok(XmlEvent::Text(t)) => {
+                if let Some(open) = stack.last_mut() {
+                    open.1.push_str(&t.unescape().unwrap_or_default());
                 }
-                b"Security" => {
-                    for attr in e.attributes().with_checks(false) {
-                        if let Ok(attr) = attr {
-                            if attr.key.as_ref() == b"UserID" {
-                                record.user = attr.unescape_value().unwrap_or_default().to_string();
-                            }
-                        }
-                    }
+            }
+            Ok(XmlEvent::CData(c)) => {
+                if let Some(open) = stack.last_mut() {
+                    open.1.push_str(&String::from_utf8_lossy(&c));
                 }
-                b"Data" => {
-                    if let Ok(XmlEvent::Text(e)) = reader.read_event_into(&mut buf) {
-                        let data = e.unescape().unwrap_or_default().to_string();
-                        if !record.description.is_empty() {
-                            record.description.push_str("; ");
-                        }
-                        record.description.push_str(&data);
-                    }
+            }
+            // The end of </UserData> itself
+            Ok(XmlEvent::End(_)) if stack.is_empty() => break,
+            Ok(XmlEvent::End(_)) => {
+                if let Some((name, text, false)) = stack.pop() {
+                    push_data(record, Some(name), text);
                 }
-                b"Channel" => {
-                    if let Ok(XmlEvent::Text(e)) = reader.read_event_into(&mut buf) {
-                        record.log_name = e.unescape().unwrap_or_default().to_string();
-                    }
+            }
+            Ok(XmlEvent::Eof) | Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+/// Reads the children of `<RenderingInfo>` through its end tag
+fn read_rendering_info(reader: &mut Reader<&[u8]>) -> RenderingInfo {
+    let mut buf = Vec::new();
+    let mut info = RenderingInfo::default();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(XmlEvent::Start(ref e)) => match e.local_name().as_ref() {
+                b"Message" => info.message = element_text(reader),
+                b"Level" => info.level = element_text(reader),
+                b"Task" => info.task = element_text(reader),
+                b"Opcode" => info.opcode = element_text(reader),
+                b"Keyword" => info.keywords.push(element_text(reader)),
+                // Keywords only wraps the Keyword elements
+                b"Keywords" => {}
+                _ => {
+                    element_text(reader);
                 }
-                _ => {}
             },
-            Ok(XmlEvent::Eof) => break,
-            Err(_) => break,
+            Ok(XmlEvent::End(ref e)) if e.local_name().as_ref() == b"RenderingInfo" => break,
+            Ok(XmlEvent::Eof) | Err(_) => break,
             _ => {}
         }
         buf.clear();
     }
-    Some(record)
+    info
+}
+
+fn data_name(e: &BytesStart) -> Option<String> {
+    e.attributes().with_checks(false).flatten()
+        .find(|attr| attr.key.as_ref() == b"Name")
+        .map(|attr| attr.unescape_value().unwrap_or_default().to_string())
+}
+
+/// Reads the attribute-only System elements, which wevtutil usually writes
+/// self-closed (`<Execution ProcessID='4' ThreadID='8'/>`)
+fn read_system_attributes(e: &BytesStart, record: &mut EventRecord) {
+    let element = e.name();
+    for attr in e.attributes().with_checks(false).flatten() {
+        let value = attr.unescape_value().unwrap_or_default();
+        match (element.as_ref(), attr.key.as_ref()) {
+            (b"Provider", b"Name") => record.source = value.to_string(),
+            (b"Provider", b"Guid") => record.provider_guid = Some(value.to_string()),
+            (b"TimeCreated", b"SystemTime") => {
+                if let Ok(dt) = DateTime::parse_from_rfc3339(&value) {
+                    record.time_created = dt.with_timezone(&Local);
+                }
+            }
+            (b"Security", b"UserID") => record.user = value.to_string(),
+            (b"Correlation", b"ActivityID") => record.activity_id = value.to_string(),
+            (b"Execution", b"ProcessID") => record.process_id = value.parse().unwrap_or(0),
+            (b"Execution", b"ThreadID") => record.thread_id = value.parse().unwrap_or(0),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECURITY_4625: &str = include_str!("../tests/fixtures/security_4625.xml");
+    const APPLICATION_ERROR: &str = include_str!("../tests/fixtures/application_error.xml");
+
+    fn with_level(level: &str) -> String {
+        format!("<Event><System><EventID>1</EventID><Level>{}</Level></System></Event>", level)
+    }
+
+    #[test]
+    fn parses_security_4625() {
+        let ev = parse_event(SECURITY_4625, "").expect("event");
+        assert_eq!(ev.event_id, 4625);
+        assert_eq!(ev.level, "Audit Failure");
+        assert_eq!(ev.audit_result(), Some("Audit Failure"));
+        assert_eq!((ev.task_display.as_str(), ev.opcode_display.as_str()), ("Logon", "Info"));
+        assert_eq!(ev.keyword_names, vec!["Audit Failure".to_string()]);
+        assert_eq!(ev.source, "Microsoft-Windows-Security-Auditing");
+        assert_eq!(ev.provider_guid.as_deref(), Some("{54849625-5478-4994-A5BA-3E3B0328C30D}"));
+        assert_eq!(ev.computer, "DC01.corp.example");
+        assert_eq!(ev.user, "S-1-5-18");
+        assert_eq!(ev.log_name, "Security");
+        assert_eq!(ev.record_id, 982134);
+        assert_eq!(ev.task, 12544);
+        assert_eq!((ev.process_id, ev.thread_id), (716, 3312));
+        assert_eq!(ev.time_created.timestamp(), 1683195330);
+        assert_eq!(ev.data_value("TargetUserName"), Some("alice"));
+        assert_eq!(ev.logon_type().as_deref(), Some("Network (3)"));
+    }
+
+    #[test]
+    fn maps_numeric_levels_and_keeps_named_ones() {
+        for (raw, expected) in [("1", "Critical"), ("2", "Error"), ("3", "Warning"), ("4", "Information"), ("5", "Verbose")] {
+            let ev = parse_event(&with_level(raw), "").unwrap();
+            assert_eq!(ev.level, expected);
+            assert_eq!(ev.level_num.to_string(), raw);
+        }
+        assert_eq!(parse_event(&with_level("Warning"), "").unwrap().level, "Warning");
+        assert_eq!(parse_event(&with_level("0"), "").unwrap().level, "0");
+    }
+
+    #[test]
+    fn imported_xml_keeps_level_name_and_number() {
+        let (events, _) = parse_events_xml(&format!("<Events>{}</Events>", with_level("2")), "export.xml");
+        assert_eq!((events[0].level.as_str(), events[0].level_num), ("Error", 2));
+    }
+
+    #[test]
+    fn missing_time_created_defaults_to_now() {
+        let before = Local::now();
+        let ev = parse_event(APPLICATION_ERROR, "").expect("event");
+        assert!(ev.time_created >= before && ev.time_created <= Local::now());
+        assert_eq!(ev.event_id, 1000);
+        assert_eq!(ev.level, "Error");
+        assert_eq!(ev.description, "app.exe; 1.0.0.0");
+    }
+
+    #[test]
+    fn decodes_bom_utf16_and_ansi_output() {
+        let text = "<Event>Überprüfung</Event>";
+        let utf16: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        assert_eq!(decode_output(&[b"\xEF\xBB\xBF".as_slice(), text.as_bytes()].concat()), text);
+        assert_eq!(decode_output(&[b"\xFF\xFE".as_slice(), &utf16].concat()), text);
+        assert_eq!(decode_output(&utf16), text);
+        assert_eq!(decode_output(text.as_bytes()), text);
+        assert_eq!(decode_output(b"<Event>\xDCberpr\xFCfung</Event>"), text);
+    }
+
+    #[test]
+    fn parses_each_event_of_an_xml_export() {
+        let export = format!("<?xml version='1.0'?>\n<Events>\n{}\n{}\n<Event><System><EventID>7", SECURITY_4625, APPLICATION_ERROR);
+        let (events, error) = parse_events_xml(&export, "export.xml");
+        assert_eq!(events.len(), 2);
+        assert_eq!((events[0].event_id, events[0].user.as_str()), (4625, "S-1-5-18"));
+        assert_eq!(events[0].data_value("TargetUserName"), Some("alice"));
+        assert_eq!(events[0].raw_xml, SECURITY_4625.trim_end());
+        assert_eq!((events[1].event_id, events[1].log_name.as_str()), (1000, "Application"));
+        assert!(error.is_some(), "the cut-off third event is reported");
+    }
+
+    #[test]
+    fn empty_elements_do_not_shift_fields() {
+        let xml = "<Event><System><EventID/><Version></Version><Level>2</Level><Task>7</Task>\
+                   <Computer/></System><EventData><Data Name='A'/><Data Name='B'></Data><Data Name='C'>c</Data></EventData></Event>";
+        let ev = parse_event(xml, "").unwrap();
+        assert_eq!(ev.event_id, 0);
+        assert_eq!(ev.version, 0);
+        assert_eq!(ev.level, "Error");
+        assert_eq!(ev.task, 7);
+        assert_eq!(ev.computer, "");
+        assert_eq!(ev.data, vec![("A".into(), String::new()), ("B".into(), String::new()), ("C".into(), "c".into())]);
+        assert_eq!(ev.description, "c");
+    }
+
+    #[test]
+    fn reads_user_data_fields() {
+        let xml = "<Event><System><EventID>1102</EventID></System><UserData>\
+                   <LogFileCleared xmlns='http://manifests.microsoft.com/win/2004/08/windows/eventlog'>\
+                   <SubjectUserName>alice</SubjectUserName><SubjectLogonId/><Client><Address>10.0.0.5</Address></Client>\
+                   </LogFileCleared></UserData></Event>";
+        let ev = parse_event(xml, "").expect("event");
+        assert_eq!(ev.event_id, 1102);
+        assert_eq!(ev.data, vec![
+            ("SubjectUserName".to_string(), "alice".to_string()),
+            ("SubjectLogonId".to_string(), String::new()),
+            ("Address".to_string(), "10.0.0.5".to_string()),
+        ]);
+        assert_eq!(ev.description, "alice; 10.0.0.5");
+    }
+
+    #[test]
+    fn decodes_binary_payload() {
+        let xml = "<Event><System><EventID>7</EventID></System><EventData><Data>x</Data><Binary>4869000A</Binary></EventData></Event>";
+        let ev = parse_event(xml, "").expect("event");
+        assert_eq!(ev.binary, "4869000A");
+        assert_eq!(ev.binary_bytes(), Some(vec![0x48, 0x69, 0x00, 0x0A]));
+        assert_eq!(EventRecord { binary: "4G".into(), ..Default::default() }.binary_bytes(), None);
+    }
+
+    #[test]
+    fn keeps_sub_second_system_time() {
+        let xml = "<Event><System><TimeCreated SystemTime='2024-03-01T10:15:30.1234567Z'/></System></Event>";
+        let ev = parse_event(xml, "").expect("event");
+        assert_eq!(ev.time_created.timestamp_subsec_nanos(), 123_456_700);
+    }
+
+    #[test]
+    fn reads_rendering_info_without_clobbering_system() {
+        let xml = "<Event><System><EventID>4624</EventID><Level>0</Level><Task>12544</Task>\
+                   <Keywords>0x8020000000000000</Keywords><Channel>Security</Channel></System>\
+                   <RenderingInfo Culture='en-US'><Message>An account was successfully logged on.</Message>\
+                   <Level>Information</Level><Task>Logon</Task><Opcode>Info</Opcode><Channel>Security</Channel>\
+                   <Keywords><Keyword>Audit Success</Keyword></Keywords></RenderingInfo></Event>";
+        let ev = parse_event(xml, "").expect("event");
+        assert_eq!((ev.task, ev.keywords.as_str(), ev.level.as_str()), (12544, "0x8020000000000000", "Audit Success"));
+        let info = ev.rendering.as_ref().expect("rendering info");
+        assert_eq!(info.message, "An account was successfully logged on.");
+        assert_eq!(ev.description, info.message);
+        assert_eq!(info.keywords, vec!["Audit Success".to_string()]);
+        assert_eq!(ev.task_label(), "Logon");
+    }
+
+    #[test]
+    fn parses_channel_info() {
+        let gl = "name: Security\r\nenabled: true\r\ntype: Admin\r\nlogging:\r\n  \
+                  logFileName: %SystemRoot%\\System32\\Winevt\\Logs\\Security.evtx\r\n  \
+                  retention: false\r\n  autoBackup: false\r\n  maxSize: 20971520\r\npublishing:\r\n  fileMax: 1\r\n";
+        let gli = "creationTime: 2024-01-01T00:00:00.000Z\r\nfileSize: 20975616\r\nnumberOfLogRecords: 41230\r\n";
+        let info = parse_channel_info(gl, gli);
+        assert!(info.enabled);
+        assert_eq!(info.log_file, r"%SystemRoot%\System32\Winevt\Logs\Security.evtx");
+        assert_eq!((info.max_size, info.file_size, info.record_count), (Some(20_971_520), Some(20_975_616), Some(41_230)));
+        assert_eq!(info.retention_policy(), "Overwrite events as needed");
+        assert!(info.is_full());
+    }
+
+    #[test]
+    fn streams_events_across_chunk_boundaries() {
+        let one = "<Event><System><EventID>1</EventID><EventRecordID>10</EventRecordID></System></Event>\r\n";
+        let two = "<Event><System><EventID>2</EventID><EventRecordID>11</EventRecordID></System>\
+                   <EventData><Data Name='Note'>caf\u{e9}</Data></EventData></Event>\r\n";
+        let output: Vec<u8> = format!("{}{}", one, two).encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let mut stream = EventStream::default();
+        let mut ids = Vec::new();
+        for chunk in output.chunks(7) {
+            ids.extend(stream.push(chunk, false).iter().map(|e| e.record_id));
+        }
+        ids.extend(stream.push(&[], true).iter().map(|e| e.record_id));
+        assert_eq!(ids, vec![10, 11]);
+
+        let mut stream = EventStream { channel: "Application".to_string(), ..Default::default() };
+        let utf8 = two.as_bytes();
+        let cut = utf8.iter().position(|&b| b == 0xC3).expect("é") + 1;
+        assert!(stream.push(&utf8[..cut], false).is_empty());
+        let events = stream.push(&utf8[cut..], true);
+        assert_eq!(events[0].data_value("Note"), Some("caf\u{e9}"));
+        assert_eq!(events[0].log_name, "Application");
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn queries_several_channels_separately() {
+        let logs = vec!["Application".to_string(), "System".to_string()];
+        let (events, errors) = query_channels(&logs, 5);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert!(!events.is_empty());
+        assert!(events.iter().all(|e| logs.contains(&e.log_name)));
+        assert!(events.windows(2).all(|w| w[0].time_created >= w[1].time_created));
+    }
+
+    #[test]
+    fn fills_message_templates() {
+        let output = "name: Contoso-App\r\nevents:\r\nevent:\r\n  value: 1000\r\n  version: 0\r\n  level: 2\r\n  \
+                      message: Job %1 failed:%n%t%2!s! (%3)\r\nRetry later.\r\n\r\nevent:\r\n  value: 1001\r\n  version: 1\r\n";
+        let templates = parse_provider_templates(output);
+        assert_eq!(templates.len(), 1);
+        let template = &templates[&(1000, 0)];
+        assert_eq!(template, "Job %1 failed:%n%t%2!s! (%3)\nRetry later.");
+        assert_eq!(format_template(template, &["backup", "disk full"]), "Job backup failed:\n\tdisk full (%3)\nRetry later.");
+        assert_eq!(format_template("100%% of %1", &["x"]), "100% of x");
+    }
+
+    #[test]
+    fn malformed_xml_does_not_panic() {
+        for xml in ["", "<Event><System><EventID>12</Sys", "<<>>", "<Event><EventID>abc</EventID></Event>", &SECURITY_4625[..200]] {
+            let _ = parse_event(xml, "");
+        }
+        assert_eq!(parse_event("<Event><EventID>abc</EventID></Event>", "").unwrap().event_id, 0);
+    }
 }
@@ -0,0 +1,143 @@
+use std::io::Write;
+use quick_xml::escape::escape;
+use crate::event_log::EventRecord;
+use crate::timeline::{TimelineBucket, TIMELINE_LEVELS};
+
+/// Concatenates the raw XML of `events` under a single `<Events>` root.
+/// Records whose raw text isn't XML (e.g. syslog lines) are escaped into an `<Event>` element.
+pub fn events_to_xml(events: &[EventRecord]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str(&format!("<Events count=\"{}\">\n", events.len()));
+    for ev in events {
+        let raw = ev.raw_xml.trim();
+        if raw.starts_with('<') {
+            xml.push_str(raw);
+        } else {
+            xml.push_str(&format!("<Event>{}</Event>", escape(raw)));
+        }
+        xml.push('\n');
+    }
+    xml.push_str("</Events>\n");
+    xml
+}
+
+/// Writes each event's raw XML to its own file in `dir`, named
+/// `<log>_<RecordID>_<timestamp>.xml`; returns how many files were written
+pub fn events_to_files(events: &[EventRecord], dir: &std::path::Path) -> std::io::Result<usize> {
+    let safe = |s: &str| s.chars().map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' }).collect::<String>();
+    for (i, ev) in events.iter().enumerate() {
+        // Events without a RecordID (text imports) are numbered by position instead
+        let id = if ev.record_id != 0 { ev.record_id.to_string() } else { format!("n{}", i + 1) };
+        let name = format!("{}_{}_{}.xml", safe(&ev.log_name), id, ev.time_created.format("%Y%m%dT%H%M%S%.3f"));
+        let raw = ev.raw_xml.trim();
+        let xml = if raw.starts_with('<') { raw.to_string() } else { format!("<Event>{}</Event>", escape(raw)) };
+        std::fs::write(dir.join(name), xml)?;
+    }
+    Ok(events.len())
+}
+
+/// Renders events as a GitHub-flavored Markdown table (Time | Level | ID | Source | Message),
+/// cutting messages longer than `message_width` characters with an ellipsis. `time_format`
+/// must be a valid chrono format.
+pub fn events_to_markdown(events: &[EventRecord], message_width: usize, time_format: &str) -> String {
+    let cell = |text: &str| text.replace('|', "\\|").replace(['\r', '\n'], " ");
+    let mut md = String::from("| Time | Level | ID | Source | Message |\n|---|---|---|---|---|\n");
+    for ev in events {
+        let message = ev.description.trim();
+        let message = if message.chars().count() > message_width {
+            format!("{}…", message.chars().take(message_width.saturating_sub(1)).collect::<String>())
+        } else {
+            message.to_string()
+        };
+        md.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            cell(&ev.time_created.format(time_format).to_string()),
+            cell(&ev.level),
+            ev.event_id,
+            cell(&ev.source),
+            cell(&message),
+        ));
+    }
+    md
+}
+
+/// Every named data field across `events`, in the order first seen; used as extra export columns
+pub fn data_keys(events: &[EventRecord]) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    for (name, _) in events.iter().flat_map(|ev| &ev.data) {
+        if !keys.contains(name) {
+            keys.push(name.clone());
+        }
+    }
+    keys
+}
+
+/// Renders events as a pretty-printed JSON array. With `include_data` each object
+/// also carries its named data fields as a nested `data` object.
+pub fn events_to_json(events: &[EventRecord], include_data: bool) -> String {
+    let objects: Vec<serde_json::Value> = events.iter().map(|ev| event_json(ev, include_data)).collect();
+    serde_json::to_string_pretty(&objects).unwrap_or_default()
+}
+
+/// Writes events as JSON Lines, one compact object per line with the members of
+/// events_to_json, straight to `path` so a large export is never held as one string
+pub fn events_to_jsonl(events: &[EventRecord], include_data: bool, path: &std::path::Path) -> std::io::Result<()> {
+    let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+    for ev in events {
+        serde_json::to_writer(&mut out, &event_json(ev, include_data))?;
+        out.write_all(b"\n")?;
+    }
+    out.flush()
+}
+
+fn event_json(ev: &EventRecord, include_data: bool) -> serde_json::Value {
+    let mut object = serde_json::json!({
+        "time": ev.time_created.to_rfc3339(),
+        "log": ev.log_name,
+        "record_id": ev.record_id,
+        "level": ev.level,
+        "event_id": ev.event_id,
+        "source": ev.source,
+        "task": ev.task,
+        "keywords": ev.keywords,
+        "user": ev.user,
+        "computer": ev.computer,
+        "description": ev.description,
+    });
+    if include_data {
+        let data: serde_json::Map<String, serde_json::Value> =
+            ev.data.iter().map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone()))).collect();
+        object["data"] = serde_json::Value::Object(data);
+    }
+    object
+}
+
+/// Writes one CSV row per item of `rows` under `header`, in the order given
+pub fn rows_to_csv<I>(header: &[&str], rows: I, path: &std::path::Path) -> Result<(), csv::Error>
+where
+    I: IntoIterator<Item = Vec<String>>,
+{
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record(header)?;
+    for row in rows {
+        writer.write_record(&row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes timeline buckets as CSV: bucket start, total, then one column per level
+pub fn timeline_to_csv(buckets: &[TimelineBucket], path: &std::path::Path) -> Result<(), csv::Error> {
+    let mut writer = csv::Writer::from_path(path)?;
+    let mut header = vec!["bucket_start", "total"];
+    header.extend(TIMELINE_LEVELS);
+    header.push("Other");
+    writer.write_record(&header)?;
+    for bucket in buckets {
+        let mut row = vec![bucket.start.format("%Y-%m-%d %H:%M:%S").to_string(), bucket.total.to_string()];
+        row.extend(bucket.per_level.iter().map(|n| n.to_string()));
+        writer.write_record(&row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use regex::Regex;
+use crate::event_log::EventRecord;
+use crate::text_import::parse_text_log;
+
+/// Tails a text log that another process is writing, turning each appended line
+/// into an event with the text import pattern. Watching stops when this is dropped.
+pub struct FileWatch {
+    _watcher: RecommendedWatcher,
+    events: Receiver<EventRecord>,
+}
+
+impl FileWatch {
+    /// Reads `path` as it is now and starts watching it for appended lines.
+    /// Returns the watch along with the events already in the file.
+    pub fn start(path: &Path, pattern: Regex, log_name: String) -> Result<(Self, Vec<EventRecord>), String> {
+        let path = path.canonicalize().map_err(|e| e.to_string())?;
+        let mut tail = Tail { path: path.clone(), offset: 0, line: 0, pending: Vec::new(), pattern, log_name };
+        let existing = tail.read_new()?;
+        let (tx, rx) = channel();
+        let watched = path.clone();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            let Ok(event) = res else {
+                return;
+            };
+            if !event.paths.iter().any(|p| p == &watched) || event.kind.is_access() {
+                return;
+            }
+            for ev in tail.read_new().unwrap_or_default() {
+                if tx.send(ev).is_err() {
+                    return;
+                }
+            }
+        })
+        .map_err(|e| e.to_string())?;
+        // The directory rather than the file, so a log that is rotated or recreated is picked up again
+        let dir = path.parent().unwrap_or(&path);
+        watcher.watch(dir, RecursiveMode::NonRecursive).map_err(|e| e.to_string())?;
+        Ok((Self { _watcher: watcher, events: rx }, existing))
+    }
+
+    /// Next event parsed from an appended line, if any arrived
+    pub fn try_recv(&self) -> Option<EventRecord> {
+        self.events.try_recv().ok()
+    }
+}
+
+/// Read position in the watched file
+struct Tail {
+    path: PathBuf,
+    offset: u64,
+    /// Lines parsed so far; numbers each event's record_id so repeated lines stay distinct
+    line: u64,
+    /// Bytes after the last newline, held until the line is complete
+    pending: Vec<u8>,
+    pattern: Regex,
+    log_name: String,
+}
+
+impl Tail {
+    fn read_new(&mut self) -> Result<Vec<EventRecord>, String> {
+        let mut file = File::open(&self.path).map_err(|e| e.to_string())?;
+        let len = file.metadata().map_err(|e| e.to_string())?.len();
+        if len < self.offset {
+            // Truncated or replaced: start over from the top
+            self.offset = 0;
+            self.pending.clear();
+        }
+        file.seek(SeekFrom::Start(self.offset)).map_err(|e| e.to_string())?;
+        let read = file.read_to_end(&mut self.pending).map_err(|e| e.to_string())?;
+        self.offset += read as u64;
+        let Some(end) = self.pending.iter().rposition(|&b| b == b'\n') else {
+            return Ok(Vec::new());
+        };
+        let complete: Vec<u8> = self.pending.drain(..=end).collect();
+        let mut events = parse_text_log(&String::from_utf8_lossy(&complete), &self.pattern, &self.log_name);
+        for ev in &mut events {
+            self.line += 1;
+            ev.record_id = self.line;
+        }
+        Ok(events)
+    }
+}
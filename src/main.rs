@@ -1,29 +1,262 @@
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
 use std::fs::File;
 use std::io::Read;
-use std::sync::mpsc::{channel, Receiver};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
 use std::thread;
-use chrono::{NaiveDate, Local, TimeZone};
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
 use eframe::{egui, App, Frame};
 use egui_extras::{Column, TableBuilder};
-use crate::event_log::{EventRecord, list_event_logs, query_events};
-use evtx::EvtxParser;
+use crate::event_log::{channel_info, format_template, provider_templates, ChannelInfo, EventRecord, MessageTemplates, KEYWORD_AUDIT_FAILURE, KEYWORD_AUDIT_SUCCESS, LEVELS, level_severity, list_event_logs, open_in_event_viewer, parse_events_xml, query_channels, query_events_progress, query_record, query_structured, read_evtx, read_evtx_progress, resolve_sid, set_description_format, set_query_timeout, set_rendered_xml, well_known_sid};
 use csv::ReaderBuilder;
-use quick_xml::events::Event as XmlEvent;
+use crate::anonymize::Anonymizer;
+use crate::file_watch::FileWatch;
+use crate::search_index::{index_text, IndexText, SearchIndex};
+use crate::text_import::{parse_text_log, TEXT_IMPORT_PRESETS};
 
+mod anonymize;
 mod event_log;
+mod export;
+mod file_watch;
+mod search_index;
+mod sound;
+mod text_import;
+mod timeline;
 
 #[derive(Default)]
 struct Filters {
     levels: Vec<String>,
+    audit: AuditFilter,
     source: String,
-    event_id: Option<u16>,
+    /// Event IDs to show; empty shows every ID
+    event_ids: HashSet<u16>,
+    task: Option<u16>,
     user: String,
-    computer: String,
+    /// Hosts to show; empty shows every computer
+    computers: BTreeSet<String>,
     keyword: String,
+    keyword_mode: KeywordMode,
+    keyword_scope: KeywordScope,
+    /// Text filters compare letter case only when set
+    match_case: bool,
+    /// Keyword terms only match as whole words, so "net" skips "network"
+    whole_word: bool,
     date_from: Option<NaiveDate>,
     date_to: Option<NaiveDate>,
+    /// Time-of-day window applied on every day; wraps past midnight when time_from is later
+    time_from: Option<NaiveTime>,
+    time_to: Option<NaiveTime>,
+    hide_empty_descriptions: bool,
+    /// Only events missing from the loaded baseline; ignored without one
+    new_since_baseline: bool,
+    /// Per-column text typed in the filter row under the table header
+    columns: HashMap<TableColumn, String>,
 }
 
+/// Which fields the keyword filter searches
+struct KeywordScope {
+    description: bool,
+    raw_xml: bool,
+    source: bool,
+    user: bool,
+}
+
+impl Default for KeywordScope {
+    fn default() -> Self {
+        Self { description: true, raw_xml: true, source: false, user: false }
+    }
+}
+
+/// Which audit outcomes are shown; anything but All hides events that aren't audits
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum AuditFilter {
+    #[default]
+    All,
+    Success,
+    Failure,
+    Both,
+}
+
+impl AuditFilter {
+    fn label(self) -> &'static str {
+        match self {
+            AuditFilter::All => "All events",
+            AuditFilter::Success => "Audit Success only",
+            AuditFilter::Failure => "Audit Failure only",
+            AuditFilter::Both => "Audit Success and Failure",
+        }
+    }
+
+    fn matches(self, ev: &EventRecord) -> bool {
+        match (self, ev.audit_result()) {
+            (AuditFilter::All, _) => true,
+            (AuditFilter::Success, result) => result == Some("Audit Success"),
+            (AuditFilter::Failure, result) => result == Some("Audit Failure"),
+            (AuditFilter::Both, result) => result.is_some(),
+        }
+    }
+
+    /// Keywords bits of the outcomes shown, 0 for All
+    fn keyword_bits(self) -> u64 {
+        match self {
+            AuditFilter::All => 0,
+            AuditFilter::Success => KEYWORD_AUDIT_SUCCESS,
+            AuditFilter::Failure => KEYWORD_AUDIT_FAILURE,
+            AuditFilter::Both => KEYWORD_AUDIT_SUCCESS | KEYWORD_AUDIT_FAILURE,
+        }
+    }
+}
+
+/// How multiple keyword terms combine
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum KeywordMode {
+    #[default]
+    All,
+    Any,
+}
+
+/// Levels kept by the "Errors & Warnings" quick toggle
+const PROBLEM_LEVELS: [&str; 3] = ["Critical", "Error", "Warning"];
+
+/// Base interval between live polls; doubled per consecutive failure
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Longest the poller waits between retries while queries keep failing
+const POLL_MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How long a log's query results are reused when toggling logs on and off
+const QUERY_CACHE_TTL: Duration = Duration::from_secs(10);
+
+/// Maximum number of hits listed by "Find in Raw XML"
+const XML_SEARCH_MAX_HITS: usize = 500;
+
+/// Key under which Settings are persisted in eframe storage
+const SETTINGS_KEY: &str = "settings";
+
+/// Window title, and the name of the folder eframe keeps its storage in
+const APP_NAME: &str = "Rust Windows Event Viewer";
+
+/// Table row spacing
+#[derive(Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+enum Density {
+    Compact,
+    Normal,
+    Comfortable,
+}
+
+impl Density {
+    fn row_height(self) -> f32 {
+        match self {
+            Density::Compact => 16.0,
+            Density::Normal => 20.0,
+            Density::Comfortable => 24.0,
+        }
+    }
+
+    fn button_padding(self) -> f32 {
+        match self {
+            Density::Compact => 0.0,
+            Density::Normal => 1.0,
+            Density::Comfortable => 3.0,
+        }
+    }
+}
+
+/// User-adjustable preferences shown in the Settings window, persisted across runs
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct Settings {
+    /// URL opened by "Lookup Event ID online"; `{source}` and `{id}` are substituted
+    lookup_url_template: String,
+    show_task_column: bool,
+    show_delta_column: bool,
+    /// Salt for the table's id; bumping it drops the dragged column widths egui remembers
+    column_layout: u32,
+    /// Width of each timeline bucket in minutes
+    timeline_bucket_minutes: u32,
+    density: Density,
+    /// When off, raw_xml is dropped once an event has been parsed to save memory
+    keep_raw_xml: bool,
+    /// Play a tone when a live Critical / Error event arrives
+    sound_on_critical: bool,
+    sound_on_error: bool,
+    /// Silences all alert sounds regardless of the per-level settings
+    sound_muted: bool,
+    /// Live events below this level_severity are dropped by the poller; 0 keeps everything
+    live_min_severity: u8,
+    /// Messages longer than this many characters are cut in Markdown exports
+    markdown_message_width: usize,
+    /// Raw XML longer than this is truncated in the details panel
+    max_xml_display_kb: u32,
+    /// Regex applied to each line of imported .txt/.log files; see text_import
+    text_import_pattern: String,
+    /// Whether the details side panel is shown, and its last width
+    show_details: bool,
+    details_width: f32,
+    /// Level-colored overview strip beside the table
+    show_minimap: bool,
+    /// Query wevtutil with /f:RenderedXml for provider-formatted messages and names
+    rendered_xml: bool,
+    /// Seconds before a hung wevtutil query is killed; 0 for no limit
+    query_timeout_secs: u32,
+    /// Text between Data values in descriptions, and whether they are written as Name=Value
+    description_separator: String,
+    description_names: bool,
+    /// chrono format string for times in the table and details; see TIME_FORMAT_PRESETS
+    time_format: String,
+    /// Keep a copy of the last import on disk so it can be restored next session
+    cache_imports: bool,
+    /// Files the cached import was read from, shown in the restore offer
+    last_import_sources: Vec<String>,
+    /// Open folder tags events with the name of the folder holding their file as the computer
+    folder_host_from_parent: bool,
+    /// Source, User and Computer cells longer than this many characters end in "…"
+    max_cell_chars: usize,
+    /// Trailing window for the Error + Critical count in the status bar
+    error_badge_minutes: u32,
+    /// Starred channels, listed first in the log picker
+    favorite_channels: Vec<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            lookup_url_template: "https://www.bing.com/search?q={source}+event+id+{id}".to_string(),
+            show_task_column: false,
+            show_delta_column: false,
+            column_layout: 0,
+            timeline_bucket_minutes: 60,
+            density: Density::Normal,
+            keep_raw_xml: true,
+            sound_on_critical: false,
+            sound_on_error: false,
+            sound_muted: false,
+            live_min_severity: 0,
+            markdown_message_width: 120,
+            max_xml_display_kb: 64,
+            text_import_pattern: TEXT_IMPORT_PRESETS[0].1.to_string(),
+            show_details: true,
+            details_width: 360.0,
+            show_minimap: true,
+            rendered_xml: true,
+            query_timeout_secs: 120,
+            description_separator: "; ".to_string(),
+            description_names: false,
+            time_format: TIME_FORMAT_PRESETS[0].1.to_string(),
+            error_badge_minutes: 60,
+            max_cell_chars: 40,
+            folder_host_from_parent: true,
+            cache_imports: false,
+            last_import_sources: Vec::new(),
+            favorite_channels: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
 enum SortBy { Time, Level, EventID, Source }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -38,6 +271,280 @@ enum ThemeMode {
     Nord,
 }
 
+/// Sliding window for the live event rate, split into RATE_BUCKETS sparkline bars
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+const RATE_BUCKETS: usize = 12;
+
+/// Live events held while paused; beyond this the oldest are dropped
+const PAUSE_BUFFER_MAX: usize = 5000;
+
+/// Minimum gap between alert sounds, so a burst of errors beeps once
+const SOUND_COOLDOWN: Duration = Duration::from_secs(10);
+
+/// Pause in typing after which the next key starts a new type-ahead search
+const TYPE_AHEAD_IDLE: Duration = Duration::from_secs(1);
+
+/// Rows shown by the "First 100" / "Last 100" buttons
+const QUICK_LIMIT: usize = 100;
+
+/// Temporary clamp of the table to the head or tail of filtered_events
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ViewLimit {
+    All,
+    First(usize),
+    Last(usize),
+}
+
+/// One table row: an index into filtered_events, plus the length of the run
+/// of identical events it heads when duplicates are collapsed
+struct ViewRow {
+    index: usize,
+    run: usize,
+    expanded: bool,
+    /// Set on a day heading when grouping by day: the number of events that day,
+    /// starting at index
+    day: Option<usize>,
+}
+
+/// Identifies a run of duplicates by its first event, stable across re-filtering
+type RunKey = (i64, u16, String);
+
+fn run_key(ev: &EventRecord) -> RunKey {
+    (ev.time_created.timestamp(), ev.event_id, ev.source.clone())
+}
+
+/// A table column, in display order as returned by visible_columns
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum TableColumn {
+    Time,
+    /// Time since the row above, in the current sort
+    Delta,
+    /// Channel the event came from, to tell rows apart when several logs are merged
+    Log,
+    Level,
+    EventId,
+    Source,
+    User,
+    Computer,
+    Task,
+    /// An EventData field chosen in the column picker
+    Data(String),
+}
+
+impl TableColumn {
+    fn title(&self) -> &str {
+        match self {
+            TableColumn::Time => "Time",
+            TableColumn::Delta => "Δt",
+            TableColumn::Log => "Log",
+            TableColumn::Level => "Level",
+            TableColumn::EventId => "ID",
+            TableColumn::Source => "Source",
+            TableColumn::User => "Username",
+            TableColumn::Computer => "Computer",
+            TableColumn::Task => "Task Category",
+            TableColumn::Data(name) => name,
+        }
+    }
+
+    fn sort_by(&self) -> Option<SortBy> {
+        match self {
+            TableColumn::Time => Some(SortBy::Time),
+            TableColumn::Level => Some(SortBy::Level),
+            TableColumn::EventId => Some(SortBy::EventID),
+            TableColumn::Source => Some(SortBy::Source),
+            _ => None,
+        }
+    }
+
+    fn initial_width(&self) -> f32 {
+        match self {
+            TableColumn::Level | TableColumn::EventId | TableColumn::Delta => 60.0,
+            TableColumn::Source | TableColumn::Log => 100.0,
+            TableColumn::Computer => 180.0,
+            TableColumn::Task => 140.0,
+            _ => 120.0,
+        }
+    }
+
+    /// Cell text for `ev`, as shown in the table and written to CSV. Delta depends on
+    /// the neighbouring row rather than the event alone, so callers use delta_text for it.
+    fn text(&self, ev: &EventRecord, time_format: &str) -> String {
+        match self {
+            TableColumn::Time => format_time(&ev.time_created, time_format),
+            TableColumn::Delta => String::new(),
+            TableColumn::Log => ev.log_name.clone(),
+            TableColumn::Level => ev.level.clone(),
+            TableColumn::EventId => ev.event_id.to_string(),
+            TableColumn::Source => ev.source.clone(),
+            TableColumn::User => ev.user.clone(),
+            TableColumn::Computer => ev.computer.clone(),
+            TableColumn::Task => ev.task_label(),
+            TableColumn::Data(name) => ev.data_value(name).unwrap_or_default().to_string(),
+        }
+    }
+}
+
+/// Time formats offered in Settings, as (name, chrono format string)
+const TIME_FORMAT_PRESETS: [(&str, &str); 5] = [
+    ("Default", "%Y-%m-%d %H:%M:%S"),
+    ("ISO 8601", "%Y-%m-%dT%H:%M:%S%.3f%:z"),
+    ("US", "%m/%d/%Y %I:%M:%S%.3f %p"),
+    ("EU", "%d.%m.%Y %H:%M:%S"),
+    ("Epoch", "%s"),
+];
+
+/// Whether chrono can format with `format`; formatting with a bad one panics
+fn valid_time_format(format: &str) -> bool {
+    !chrono::format::StrftimeItems::new(format).any(|item| item == chrono::format::Item::Error)
+}
+
+/// The user's time format, or the default one if that doesn't parse
+fn checked_time_format(format: &str) -> String {
+    if valid_time_format(format) { format } else { TIME_FORMAT_PRESETS[0].1 }.to_string()
+}
+
+/// `time` in a format already passed through checked_time_format
+fn format_time(time: &DateTime<Local>, format: &str) -> String {
+    time.format(format).to_string()
+}
+
+/// Whether the description or source of `ev` contains the lowercase `needle`
+fn type_ahead_match(ev: &EventRecord, needle: &str) -> bool {
+    ev.description.to_lowercase().contains(needle) || ev.source.to_lowercase().contains(needle)
+}
+
+/// Separators offered for joining Data values into a description
+const DESCRIPTION_SEPARATORS: [&str; 4] = ["; ", ", ", " | ", "\n"];
+
+fn separator_label(separator: &str) -> &str {
+    match separator {
+        "\n" => "New line",
+        "; " => "Semicolon",
+        ", " => "Comma",
+        " | " => "Pipe",
+        other => other,
+    }
+}
+
+/// Name for a level_severity threshold in the live filter setting
+fn severity_label(severity: u8) -> &'static str {
+    match severity {
+        0 => "Everything",
+        1 => "Verbose",
+        2 => "Information",
+        3 => "Warning",
+        4 => "Error",
+        _ => "Critical",
+    }
+}
+
+/// Text color for a level name, falling back to the normal text color
+fn level_color(level: &str, visuals: &egui::Visuals) -> egui::Color32 {
+    match level {
+        "Critical" | "Error" => visuals.error_fg_color,
+        "Warning" => visuals.warn_fg_color,
+        "Audit Failure" => egui::Color32::from_rgb(214, 93, 14),
+        "Audit Success" => egui::Color32::from_rgb(104, 157, 106),
+        _ => visuals.text_color(),
+    }
+}
+
+/// Identity of an event for spotting repeats from the poller: its record ID within
+/// the log, or its text when the source has no record IDs
+fn event_key(ev: &EventRecord) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    ev.log_name.hash(&mut hasher);
+    if ev.record_id != 0 {
+        ev.record_id.hash(&mut hasher);
+    } else {
+        ev.description.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Events count as duplicates when ID, source and message all match
+fn is_duplicate(a: &EventRecord, b: &EventRecord) -> bool {
+    a.event_id == b.event_id && a.source == b.source && a.description == b.description
+}
+
+/// Where the table was scrolled to and what was selected for a set of logs,
+/// restored when the user switches back to it
+struct LogViewState {
+    top: Option<EventRecord>,
+    selected: Option<EventRecord>,
+}
+
+/// A file used as the event source instead of the live logs
+enum FileSource {
+    /// An exported .evtx log
+    Evtx(PathBuf),
+    /// A wevtutil structured query file, run with /sq:true
+    Query(PathBuf),
+    /// A text log tailed as it grows, parsed with the text import pattern
+    Watch(PathBuf),
+}
+
+impl FileSource {
+    fn path(&self) -> &Path {
+        match self {
+            FileSource::Evtx(path) | FileSource::Query(path) | FileSource::Watch(path) => path,
+        }
+    }
+}
+
+/// Buttons under a truncated Raw XML view
+enum XmlAction {
+    ShowFull,
+    Save(String),
+}
+
+/// Messages from the polling thread to the UI
+enum PollMessage {
+    Event(Box<EventRecord>),
+    Error(String),
+    /// A poll completed successfully, whether or not it found events
+    Heartbeat,
+}
+
+/// A snapshot to compare against: the event_key of every event it holds
+struct Baseline {
+    name: String,
+    keys: HashSet<u64>,
+}
+
+/// Live-log query running on a background thread; see reload_logs
+struct PendingLoad {
+    generation: u64,
+    started: Instant,
+    /// Events of the logs the cache could answer
+    cached: Vec<EventRecord>,
+    /// Events streamed in so far; the first batch replaces what was shown before
+    received: usize,
+    /// Restore the saved selection and scroll position once loaded
+    restore_view: bool,
+}
+
+/// Messages from background queries; loads are tagged with the generation that started them
+enum LoadMessage {
+    /// Events parsed so far, shown while the query runs
+    Progress(u64, Vec<EventRecord>),
+    /// Every log's complete result, which replaces the streamed events
+    Done(u64, Vec<(String, Result<Vec<EventRecord>, String>)>),
+    /// Complete result of reading the open file source, with the number of records skipped
+    FileDone(u64, Result<(Vec<EventRecord>, usize), String>),
+    /// Search index over the events after those already indexed, tagged with index_generation
+    Indexed(u64, SearchIndex),
+    /// wevtutil gl/gli result for a channel
+    ChannelInfo(String, Result<ChannelInfo, String>),
+    /// Message templates read for a provider
+    Templates(String, Result<MessageTemplates, String>),
+    /// Full detail of an event re-queried on its own, with the event it replaces
+    Record(Box<EventRecord>, Result<Box<EventRecord>, String>),
+    /// Account name looked up for a SID
+    Sid(String, Option<String>),
+}
+
 struct EventViewerApp {
     all_events: Vec<EventRecord>,
     filtered_events: Vec<EventRecord>,
@@ -45,13 +552,106 @@ struct EventViewerApp {
     sort_by: SortBy,
     sort_desc: bool,
     selected: Option<usize>,
-    recv: Receiver<EventRecord>,
+    recv: Receiver<PollMessage>,
+    /// Most recent polling failure, cleared by the next successful poll
+    poll_error: Option<String>,
+    last_poll: Option<DateTime<Local>>,
     paused: bool,
+    /// Selects each newly arrived live event until the user picks a row
+    follow: bool,
     page_size: u32,
+    /// Logs (or the query file) whose last fetch hit page_size, so older events were left out
+    truncated: Vec<String>,
+    /// Why each selected log failed to load on the last reload
+    load_errors: Vec<String>,
     current_page: u32,
     available_logs: Vec<String>,
     selected_logs: Vec<String>,
+    /// Shared with the poller, mirrors settings.live_min_severity
+    live_min_severity: Arc<AtomicU8>,
+    /// Shared with the poller, mirrors selected_logs
+    live_logs: Arc<Mutex<Vec<String>>>,
+    /// File shown in place of the live logs; Refresh re-reads it
+    file_source: Option<FileSource>,
+    /// Tails the file of a FileSource::Watch
+    file_watch: Option<FileWatch>,
     theme_mode: ThemeMode,
+    xml_search_open: bool,
+    xml_search: String,
+    xml_search_hits: Vec<(EventRecord, String)>,
+    data_columns: Vec<String>,
+    settings: Settings,
+    settings_open: bool,
+    /// The "Reset all settings" confirmation is showing
+    confirm_reset: bool,
+    jump_to: String,
+    scroll_to_row: Option<(usize, egui::Align)>,
+    /// First table row drawn last frame, i.e. the top of the visible page
+    first_visible_row: Option<usize>,
+    /// Scroll and selection per selected-log set, keyed by the sorted log names
+    log_views: HashMap<Vec<String>, LogViewState>,
+    event_id_input: String,
+    /// Text of the time-of-day window fields, parsed into filters.time_from / time_to
+    time_from_input: String,
+    time_to_input: String,
+    task_input: String,
+    status: String,
+    /// Recent query results keyed by (log, fetch limit)
+    query_cache: HashMap<(String, u32), (Instant, Vec<EventRecord>)>,
+    /// Bumped by every reload and cancel; results from an older generation are dropped
+    query_generation: u64,
+    pending_load: Option<PendingLoad>,
+    /// Text typed while no field has focus, and when the last key came; see type_ahead
+    type_ahead: String,
+    type_ahead_at: Option<Instant>,
+    /// Offer to restore the cached import, shown from launch until taken or dismissed
+    restore_offer: bool,
+    /// Snapshot whose events are marked as old; see "Baseline"
+    baseline: Option<Baseline>,
+    load_tx: Sender<LoadMessage>,
+    load_rx: Receiver<LoadMessage>,
+    collapse_duplicates: bool,
+    /// Shows each day as a heading that expands to that day's events
+    group_by_day: bool,
+    expanded_days: HashSet<NaiveDate>,
+    view_limit: ViewLimit,
+    /// Shows the per-column filter row; its filters only apply while shown
+    show_column_filters: bool,
+    expanded_runs: HashSet<RunKey>,
+    view_rows: Vec<ViewRow>,
+    search_index: SearchIndex,
+    /// An indexing worker is running; see index_new_events
+    indexing: bool,
+    /// Bumped when the index is dropped, so a worker's result for old events is ignored
+    index_generation: u64,
+    anonymize_exports: bool,
+    /// Exports add every named data field as its own CSV column or JSON member
+    export_data_fields: bool,
+    /// Keeps pseudonyms stable across exports for the whole session
+    anonymizer: Anonymizer,
+    last_sound: Option<Instant>,
+    /// event_key of everything loaded or received, so re-polled events are skipped
+    seen_events: HashSet<u64>,
+    /// When each new live event arrived, for the rate shown in the status bar
+    live_arrivals: VecDeque<Instant>,
+    /// Live events received while paused, added to the view on resume
+    paused_buffer: VecDeque<EventRecord>,
+    /// Buffered events dropped because paused_buffer was full
+    paused_dropped: usize,
+    /// Selected event whose Raw XML is shown past the display limit, until another is selected
+    full_xml_for: Option<EventRecord>,
+    /// Account names resolved from SIDs, so each is looked up once; None while being resolved
+    sid_names: HashMap<String, Option<String>>,
+    /// wevtutil gl/gli results per channel, shown in the details panel; None while being read
+    channel_info: HashMap<String, Option<Result<ChannelInfo, String>>>,
+    /// Event whose full detail is being re-queried
+    fetching_detail: Option<EventRecord>,
+    /// Message templates per provider, for "Explain this event"; None while being read
+    templates: HashMap<String, Option<Result<MessageTemplates, String>>>,
+    /// Last title sent to the window, to avoid resetting it every frame
+    window_title: String,
+    /// settings.time_format once checked, or the default when it doesn't parse
+    time_format: String,
 }
 
 impl Default for EventViewerApp {
@@ -59,19 +659,14 @@ impl Default for EventViewerApp {
         let available_logs = list_event_logs();
         let selected_logs = available_logs.clone();
         let (tx, rx) = channel();
-        let available_logs_for_thread = available_logs.clone();
+        let (load_tx, load_rx) = channel();
+        let live_logs = Arc::new(Mutex::new(selected_logs.clone()));
+        let logs_for_thread = live_logs.clone();
+        let live_min_severity = Arc::new(AtomicU8::new(0));
+        let min_severity_for_thread = live_min_severity.clone();
         // spawn polling thread
-        thread::spawn(move || {
-            loop {
-                // simple polling: query newest 50
-                let events = query_events(&available_logs_for_thread.join(","), 50);
-                for ev in events.into_iter().rev() {
-                    let _ = tx.send(ev);
-                }
-                std::thread::sleep(std::time::Duration::from_secs(2));
-            }
-        });
-        let mut app = Self {
+        thread::spawn(move || poll_loop(logs_for_thread, tx, min_severity_for_thread));
+        Self {
             all_events: vec![],
             filtered_events: vec![],
             filters: Filters::default(),
@@ -79,279 +674,1229 @@ impl Default for EventViewerApp {
             sort_desc: true,
             selected: None,
             recv: rx,
+            poll_error: None,
+            last_poll: None,
             paused: false,
+            follow: false,
             page_size: 100,
+            truncated: Vec::new(),
+            load_errors: Vec::new(),
             current_page: 0,
             available_logs,
             selected_logs,
+            file_source: None,
+            file_watch: None,
+            live_min_severity,
+            live_logs,
             theme_mode: ThemeMode::System,
-        };
-        app.refresh_page();
-        app
+            xml_search_open: false,
+            xml_search: String::new(),
+            xml_search_hits: Vec::new(),
+            data_columns: Vec::new(),
+            settings: Settings::default(),
+            settings_open: false,
+            confirm_reset: false,
+            jump_to: String::new(),
+            scroll_to_row: None,
+            first_visible_row: None,
+            log_views: HashMap::new(),
+            event_id_input: String::new(),
+            time_from_input: String::new(),
+            time_to_input: String::new(),
+            task_input: String::new(),
+            status: String::new(),
+            query_cache: HashMap::new(),
+            query_generation: 0,
+            pending_load: None,
+            type_ahead: String::new(),
+            type_ahead_at: None,
+            restore_offer: false,
+            baseline: None,
+            load_tx,
+            load_rx,
+            window_title: String::new(),
+            time_format: TIME_FORMAT_PRESETS[0].1.to_string(),
+            collapse_duplicates: false,
+            group_by_day: false,
+            expanded_days: HashSet::new(),
+            view_limit: ViewLimit::All,
+            show_column_filters: false,
+            expanded_runs: HashSet::new(),
+            view_rows: Vec::new(),
+            sid_names: HashMap::new(),
+            channel_info: HashMap::new(),
+            templates: HashMap::new(),
+            fetching_detail: None,
+            full_xml_for: None,
+            last_sound: None,
+            seen_events: HashSet::new(),
+            live_arrivals: VecDeque::new(),
+            paused_buffer: VecDeque::new(),
+            paused_dropped: 0,
+            search_index: SearchIndex::default(),
+            indexing: false,
+            index_generation: 0,
+            anonymize_exports: false,
+            export_data_fields: false,
+            anonymizer: Anonymizer::default(),
+        }
     }
 }
 
 impl EventViewerApp {
+    /// Re-queries every selected log, bypassing the cache
     fn refresh_page(&mut self) {
-        self.current_page = 0;
-        self.all_events = self.selected_logs.iter().flat_map(|log| query_events(log, self.page_size)).collect();
-        self.apply_filters();
-    }
-
-    fn apply_filters(&mut self) {
-        let mut evs = self.all_events.clone();
-        // basic filters
-        evs.retain(|e| {
-            (self.filters.levels.is_empty() || self.filters.levels.contains(&e.level)) &&
-            (self.filters.source.is_empty() || e.source.contains(&self.filters.source)) &&
-            (self.filters.event_id.map_or(true, |id| e.event_id == id)) &&
-            (self.filters.user.is_empty() || e.user.contains(&self.filters.user)) &&
-            (self.filters.computer.is_empty() || e.computer.contains(&self.filters.computer)) &&
-            (self.filters.keyword.is_empty() || e.description.contains(&self.filters.keyword) || e.raw_xml.contains(&self.filters.keyword)) &&
-            (self.filters.date_from.map_or(true, |d| e.time_created.date_naive() >= d)) &&
-            (self.filters.date_to.map_or(true, |d| e.time_created.date_naive() <= d))
-        });
-        // Always sort by time descending (most recent first)
-        evs.sort_by(|a, b| b.time_created.timestamp().cmp(&a.time_created.timestamp()));
-        self.filtered_events = evs;
+        self.query_cache.clear();
+        self.reload_logs();
     }
 
-    fn update_live(&mut self) {
-        if !self.paused {
-            while let Ok(ev) = self.recv.try_recv() {
-                self.all_events.insert(0, ev);
+    /// Rebuilds all_events from the selected logs or the open file, reusing recent results.
+    /// Logs that aren't cached, .evtx files and query files are read on a background thread and
+    /// picked up by update_loads.
+    fn reload_logs(&mut self) {
+        self.query_generation += 1;
+        self.pending_load = None;
+        self.current_page = 0;
+        self.truncated.clear();
+        self.load_errors.clear();
+        self.file_watch = None;
+        // The reload picks these up itself
+        self.paused_buffer.clear();
+        self.paused_dropped = 0;
+        if let Some(source) = &self.file_source {
+            let result = match source {
+                FileSource::Evtx(path) | FileSource::Query(path) => {
+                    let (path, query) = (path.clone(), matches!(source, FileSource::Query(_)));
+                    let tx = self.load_tx.clone();
+                    let generation = self.query_generation;
+                    let page_size = self.page_size;
+                    in_background(&self.load_tx, move || {
+                        let mut progress = |batch: &[EventRecord]| {
+                            let _ = tx.send(LoadMessage::Progress(generation, batch.to_vec()));
+                        };
+                        let result = if query {
+                            query_structured(&path, page_size, &mut progress).map(|events| (events, 0))
+                        } else {
+                            read_evtx_progress(&path, &mut progress)
+                        };
+                        LoadMessage::FileDone(generation, result)
+                    });
+                    self.pending_load = Some(PendingLoad { generation, started: Instant::now(), cached: Vec::new(), received: 0, restore_view: false });
+                    return;
+                }
+                FileSource::Watch(path) => {
+                    let log_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                    regex::Regex::new(&self.settings.text_import_pattern)
+                        .map_err(|e| format!("Invalid line pattern: {}", e))
+                        .and_then(|pattern| FileWatch::start(path, pattern, log_name))
+                        .map(|(watch, events)| {
+                            self.file_watch = Some(watch);
+                            (events, 0)
+                        })
+                }
+            };
+            self.finish_file_load(result);
+            return;
+        }
+        self.query_cache.retain(|_, (at, _)| at.elapsed() < QUERY_CACHE_TTL);
+        let mut cached = Vec::new();
+        let mut to_fetch = Vec::new();
+        for log in &self.selected_logs {
+            let key = (log.clone(), self.page_size);
+            match self.query_cache.get(&key) {
+                Some((_, events)) => {
+                    if events.len() >= self.page_size as usize {
+                        self.truncated.push(log.clone());
+                    }
+                    cached.extend(events.iter().cloned());
+                }
+                None => to_fetch.push(log.clone()),
             }
-            self.apply_filters();
         }
+        let pending = PendingLoad { generation: self.query_generation, started: Instant::now(), cached, received: 0, restore_view: false };
+        if to_fetch.is_empty() {
+            self.finish_load(pending, Vec::new());
+            return;
+        }
+        let tx = self.load_tx.clone();
+        let generation = self.query_generation;
+        let page_size = self.page_size;
+        thread::spawn(move || {
+            let logs = to_fetch.into_iter().map(|log| {
+                let result = query_events_progress(&log, page_size, &mut |batch| {
+                    let _ = tx.send(LoadMessage::Progress(generation, batch.to_vec()));
+                });
+                (log, result)
+            }).collect();
+            let _ = tx.send(LoadMessage::Done(generation, logs));
+        });
+        self.pending_load = Some(pending);
     }
 
-    pub fn import_file(&mut self, path: &str) {
-        self.paused = true; // Pause polling when importing
-        if path.ends_with(".evtx") {
-            if let Ok(mut parser) = EvtxParser::from_path(path) {
-                self.filtered_events.clear();
-                for record in parser.records_json() {
-                    if let Ok(json) = record {
-                        let description = format!("{:?}", json);
-                        self.filtered_events.push(EventRecord {
-                            log_name: "Imported EVTX".to_string(),
-                            time_created: chrono::Local::now(),
-                            event_id: 0,
-                            level: "Info".to_string(),
-                            source: "Import".to_string(),
-                            user: String::new(),
-                            computer: String::new(),
-                            description: description.chars().take(200).collect(),
-                            raw_xml: description,
-                        });
+    /// Takes progress and results of the running background query, dropping any from a superseded one
+    fn update_loads(&mut self) {
+        let mut streamed = false;
+        while let Ok(msg) = self.load_rx.try_recv() {
+            match msg {
+                LoadMessage::Progress(generation, batch) => {
+                    let Some(pending) = self.pending_load.as_mut().filter(|p| p.generation == generation) else {
+                        continue;
+                    };
+                    let first = pending.received == 0;
+                    if first {
+                        self.all_events = pending.cached.clone();
+                        self.seen_events = self.all_events.iter().map(event_key).collect();
                     }
-                }
-            }
-        } else if path.ends_with(".xml") {
-            let mut file = File::open(path).unwrap();
-            let mut contents = String::new();
-            file.read_to_string(&mut contents).unwrap();
-            self.filtered_events.clear();
-            let mut reader = quick_xml::Reader::from_str(&contents);
-            reader.trim_text(true);
-            let mut buf = Vec::new();
-            let mut in_event = false;
-            let mut event_xml = String::new();
-            let mut fields = EventRecord {
-                log_name: "Imported XML".to_string(),
-                time_created: chrono::Local::now(),
-                event_id: 0,
-                level: String::new(),
-                source: String::new(),
-                user: String::new(),
-                computer: String::new(),
-                description: String::new(),
-                raw_xml: String::new(),
-            };
-            loop {
-                match reader.read_event_into(&mut buf) {
-                    Ok(XmlEvent::Start(ref e)) if e.name().as_ref() == b"Event" => {
-                        in_event = true;
-                        event_xml.clear();
-                        event_xml.push_str("<Event>");
-                        fields = EventRecord {
-                            log_name: "Imported XML".to_string(),
-                            time_created: chrono::Local::now(),
-                            event_id: 0,
-                            level: String::new(),
-                            source: String::new(),
-                            user: String::new(),
-                            computer: String::new(),
-                            description: String::new(),
-                            raw_xml: String::new(),
-                        };
+                    pending.received += batch.len();
+                    if first {
+                        self.reset_search_index();
                     }
-                    Ok(XmlEvent::End(ref e)) if e.name().as_ref() == b"Event" => {
-                        in_event = false;
-                        event_xml.push_str("</Event>");
-                        // Store the full XML for this event, including all nested tags and text
-                        fields.raw_xml = event_xml.clone();
-                        self.filtered_events.push(fields.clone());
-                    }
-                    Ok(XmlEvent::Text(e)) if in_event => {
-                        event_xml.push_str(&e.unescape().unwrap_or_default());
-                    }
-                    Ok(XmlEvent::CData(e)) if in_event => {
-                        event_xml.push_str(&String::from_utf8_lossy(&e.into_inner()));
-                    }
-                    Ok(XmlEvent::Start(ref e)) if in_event => {
-                        let tag_buf = String::from_utf8_lossy(e.name().as_ref()).to_string();
-                        let tag = &tag_buf;
-                        event_xml.push('<');
-                        event_xml.push_str(tag);
-                        // Write all attributes
-                        for attr in e.attributes().with_checks(false) {
-                            if let Ok(attr) = attr {
-                                event_xml.push(' ');
-                                event_xml.push_str(&String::from_utf8_lossy(attr.key.as_ref()));
-                                event_xml.push_str("=\"");
-                                event_xml.push_str(&attr.unescape_value().unwrap_or_default());
-                                event_xml.push('"');
-                            }
+                    for mut ev in batch {
+                        // A plain XML retry after failed rendering repeats events
+                        if !self.seen_events.insert(event_key(&ev)) {
+                            continue;
                         }
-                        event_xml.push('>');
-                        // Extract fields from known tags
-                        if tag == "TimeCreated" {
-                            if let Some(Ok(attr)) = e.attributes().with_checks(false).find(|a| a.as_ref().map(|a| a.key.as_ref() == b"SystemTime").unwrap_or(false)) {
-                                if let Ok(val) = attr.unescape_value() {
-                                    // Try RFC3339 first, then fallback to space-separated format
-                                    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(&val) {
-                                        fields.time_created = dt.with_timezone(&chrono::Local);
-                                    } else if let Ok(ndt) = chrono::NaiveDateTime::parse_from_str(&val, "%Y-%m-%d %H:%M:%S%.f") {
-                                        fields.time_created = match chrono::Local.from_local_datetime(&ndt) {
-                                            chrono::LocalResult::Single(dt) => dt,
-                                            _ => chrono::Local.timestamp(0, 0),
-                                        };
-                                    }
-                                }
-                            }
-                        } else if tag == "EventID" {
-                            if let Ok(XmlEvent::Text(eid)) = reader.read_event_into(&mut buf) {
-                                if let Ok(val) = eid.unescape() {
-                                    fields.event_id = val.parse().unwrap_or(0);
-                                }
-                            }
-                        } else if tag == "Level" {
-                            if let Ok(XmlEvent::Text(lvl)) = reader.read_event_into(&mut buf) {
-                                if let Ok(val) = lvl.unescape() {
-                                    fields.level = val.to_string();
-                                }
-                            }
-                        } else if tag == "Provider" {
-                            for attr in e.attributes().with_checks(false) {
-                                if let Ok(attr) = attr {
-                                    if attr.key.as_ref() == b"Name" {
-                                        fields.source = attr.unescape_value().unwrap_or_default().to_string();
-                                    }
-                                }
-                            }
-                        } else if tag == "Computer" {
-                            if let Ok(XmlEvent::Text(comp)) = reader.read_event_into(&mut buf) {
-                                if let Ok(val) = comp.unescape() {
-                                    fields.computer = val.to_string();
-                                }
-                            }
-                        } else if tag == "UserID" {
-                            if let Ok(XmlEvent::Text(user)) = reader.read_event_into(&mut buf) {
-                                if let Ok(val) = user.unescape() {
-                                    fields.user = val.to_string();
-                                }
+                        if !self.settings.keep_raw_xml {
+                            ev.raw_xml = String::new();
+                        }
+                        self.all_events.push(ev);
+                    }
+                    streamed = true;
+                }
+                LoadMessage::Done(generation, logs) => {
+                    if let Some(pending) = self.pending_load.take_if(|p| p.generation == generation) {
+                        self.finish_load(pending, logs);
+                        streamed = false;
+                    }
+                }
+                LoadMessage::FileDone(generation, result) => {
+                    if self.pending_load.take_if(|p| p.generation == generation).is_some() {
+                        self.finish_file_load(result);
+                        streamed = false;
+                    }
+                }
+                LoadMessage::ChannelInfo(channel, info) => {
+                    self.channel_info.insert(channel, Some(info));
+                }
+                LoadMessage::Templates(provider, templates) => {
+                    self.templates.insert(provider, Some(templates));
+                }
+                LoadMessage::Sid(sid, name) => {
+                    self.sid_names.insert(sid, Some(name.unwrap_or_else(|| "(could not resolve)".to_string())));
+                }
+                LoadMessage::Record(old, result) => {
+                    self.fetching_detail = None;
+                    self.status = match result {
+                        Ok(mut full) => {
+                            if !self.settings.keep_raw_xml {
+                                full.raw_xml = String::new();
                             }
-                        } else if tag == "Data" {
-                            if let Ok(XmlEvent::Text(desc)) = reader.read_event_into(&mut buf) {
-                                if let Ok(val) = desc.unescape() {
-                                    if !fields.description.is_empty() {
-                                        fields.description.push_str("; ");
-                                    }
-                                    fields.description.push_str(&val);
+                            for ev in self.all_events.iter_mut().chain(self.filtered_events.iter_mut()) {
+                                if ev.same_event(&old) {
+                                    *ev = (*full).clone();
                                 }
                             }
+                            // Re-index the replaced text on the next filter
+                            self.reset_search_index();
+                            format!("Fetched full detail of event {}", old.record_id)
                         }
+                        Err(e) => e,
+                    };
+                }
+                LoadMessage::Indexed(generation, index) => {
+                    if generation == self.index_generation {
+                        self.search_index.merge(index);
+                        self.indexing = false;
+                        // Events that arrived meanwhile
+                        self.index_new_events();
                     }
-                    Ok(XmlEvent::End(ref e)) if in_event => {
-                        event_xml.push_str("</");
-                        event_xml.push_str(&String::from_utf8_lossy(e.name().as_ref()));
-                        event_xml.push('>');
-                    }
-                    Ok(XmlEvent::Eof) => break,
-                    Err(_) => break,
-                    _ => {}
-                }
-                buf.clear();
-            }
-        } else if path.ends_with(".csv") {
-            let file = std::fs::File::open(path);
-            if let Ok(file) = file {
-                let mut rdr = ReaderBuilder::new().has_headers(true).from_reader(file);
-                self.filtered_events.clear();
-                for result in rdr.records() {
-                    if let Ok(record) = result {
-                        let description = record.iter().collect::<Vec<_>>().join(", ");
-                        self.filtered_events.push(EventRecord {
-                            log_name: "Imported CSV".to_string(),
-                            time_created: chrono::Local::now(),
-                            event_id: 0,
-                            level: "Info".to_string(),
-                            source: "Import".to_string(),
-                            user: String::new(),
-                            computer: String::new(),
-                            description: description.chars().take(200).collect(),
-                            raw_xml: description,
-                        });
+                }
+            }
+        }
+        // Once per frame rather than per batch
+        if streamed {
+            self.apply_filters();
+        }
+    }
+
+    /// Whether a background query or indexing is still to report back, so frames keep coming to collect it
+    fn background_busy(&self) -> bool {
+        self.pending_load.is_some() || self.indexing
+            || self.channel_info.values().any(Option::is_none)
+            || self.templates.values().any(Option::is_none)
+            || self.fetching_detail.is_some()
+            || self.sid_names.values().any(Option::is_none)
+    }
+
+    /// Drops the search index; needed when all_events is replaced rather than appended to
+    fn reset_search_index(&mut self) {
+        self.search_index = SearchIndex::default();
+        self.index_generation += 1;
+        self.indexing = false;
+    }
+
+    /// Indexes the events added since the last run on a worker thread; update_loads merges
+    /// the result. Until then the newer events are searched without the index.
+    fn index_new_events(&mut self) {
+        if self.all_events.len() < self.search_index.indexed() {
+            self.reset_search_index();
+        }
+        let start = self.search_index.indexed();
+        if self.indexing || start == self.all_events.len() {
+            return;
+        }
+        let texts: Vec<IndexText> = self.all_events[start..].iter().map(index_text).collect();
+        let tx = self.load_tx.clone();
+        let generation = self.index_generation;
+        thread::spawn(move || {
+            let _ = tx.send(LoadMessage::Indexed(generation, SearchIndex::build(start, &texts)));
+        });
+        self.indexing = true;
+    }
+
+    /// Stops waiting for the running query; its results are ignored when they arrive
+    fn cancel_load(&mut self) {
+        if self.pending_load.take().is_some() {
+            self.query_generation += 1;
+            self.status = "Query cancelled".to_string();
+        }
+    }
+
+    /// Shows the events read from the open file source in place of the current ones
+    fn finish_file_load(&mut self, result: Result<(Vec<EventRecord>, usize), String>) {
+        let Some(source) = &self.file_source else {
+            return;
+        };
+        let path = source.path();
+        self.all_events = match result {
+            Ok((mut events, skipped)) => {
+                if matches!(source, FileSource::Query(_)) && events.len() >= self.page_size as usize {
+                    self.truncated.push(path.display().to_string());
+                }
+                if !self.settings.keep_raw_xml {
+                    events.iter_mut().for_each(|e| e.raw_xml = String::new());
+                }
+                self.status = format!("Read {} events from {}", format_count(events.len()), path.display());
+                if skipped > 0 {
+                    self.status.push_str(&format!(" ({} skipped)", format_count(skipped)));
+                }
+                events
+            }
+            Err(e) => {
+                self.status = e;
+                Vec::new()
+            }
+        };
+        self.seen_events = self.all_events.iter().map(event_key).collect();
+        self.reset_search_index();
+        self.apply_filters();
+    }
+
+    fn finish_load(&mut self, pending: PendingLoad, fetched: Vec<(String, Result<Vec<EventRecord>, String>)>) {
+        let mut events = pending.cached;
+        let mut errors = Vec::new();
+        for (log, result) in fetched {
+            match result {
+                Ok(mut fetched) => {
+                    // A full page almost certainly means the log holds more
+                    if fetched.len() >= self.page_size as usize {
+                        self.truncated.push(log.clone());
                     }
+                    if !self.settings.keep_raw_xml {
+                        fetched.iter_mut().for_each(|e| e.raw_xml = String::new());
+                    }
+                    events.extend(fetched.iter().cloned());
+                    self.query_cache.insert((log, self.page_size), (Instant::now(), fetched));
                 }
+                Err(e) => errors.push(e),
             }
         }
+        if !errors.is_empty() {
+            self.status = errors.join("; ");
+        }
+        self.load_errors = errors;
+        self.all_events = events;
+        self.seen_events = self.all_events.iter().map(event_key).collect();
+        self.reset_search_index();
+        self.apply_filters();
+        if pending.restore_view {
+            self.restore_log_view();
+        }
     }
-}
 
-impl App for EventViewerApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut Frame) {
-        match self.theme_mode {
-            ThemeMode::System => {}, // Use default
-            ThemeMode::GruvboxDark => {
-                ctx.set_visuals(egui::Visuals::dark());
-                ctx.set_style(egui::Style {
-                    visuals: egui::Visuals {
-                        window_fill: egui::Color32::from_rgb(40, 40, 40),
-                        panel_fill: egui::Color32::from_rgb(29, 32, 33),
-                        faint_bg_color: egui::Color32::from_rgb(60, 56, 54),
-                        extreme_bg_color: egui::Color32::from_rgb(29, 32, 33),
-                        ..egui::Visuals::dark()
-                    },
-                    ..egui::Style::default()
-                });
-            },
-            ThemeMode::GruvboxLight => {
-                ctx.set_visuals(egui::Visuals::light());
-                ctx.set_style(egui::Style {
-                    visuals: egui::Visuals {
-                        window_fill: egui::Color32::from_rgb(251, 241, 199),
-                        panel_fill: egui::Color32::from_rgb(235, 219, 178),
-                        faint_bg_color: egui::Color32::from_rgb(213, 196, 161),
-                        extreme_bg_color: egui::Color32::from_rgb(235, 219, 178),
-                        ..egui::Visuals::light()
-                    },
-                    ..egui::Style::default()
-                });
-            },
-            ThemeMode::SolarizedDark => {
-                ctx.set_visuals(egui::Visuals::dark());
-                ctx.set_style(egui::Style {
-                    visuals: egui::Visuals {
-                        window_fill: egui::Color32::from_rgb(0, 43, 54),
-                        panel_fill: egui::Color32::from_rgb(7, 54, 66),
-                        faint_bg_color: egui::Color32::from_rgb(88, 110, 117),
-                        extreme_bg_color: egui::Color32::from_rgb(7, 54, 66),
-                        ..egui::Visuals::dark()
-                    },
-                    ..egui::Style::default()
-                });
+    fn apply_filters(&mut self) {
+        self.index_new_events();
+        let match_case = self.filters.match_case;
+        let fold = |text: &str| if match_case { text.to_string() } else { text.to_lowercase() };
+        let has_text = |haystack: &str, needle: &str| {
+            if match_case { haystack.contains(needle) } else { haystack.to_lowercase().contains(needle) }
+        };
+        let terms: Vec<String> = keyword_terms(&self.filters.keyword).iter().map(|t| fold(t)).collect();
+        let word_patterns: Vec<Option<regex::Regex>> = if self.filters.whole_word {
+            terms.iter()
+                .map(|t| regex::RegexBuilder::new(&whole_word_pattern(t)).case_insensitive(!match_case).build().ok())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let source = fold(&self.filters.source);
+        let user = fold(&self.filters.user);
+        // Narrow to events the index says could match before checking each one;
+        // events it doesn't cover yet all stay candidates
+        let unindexed = self.search_index.indexed()..self.all_events.len();
+        let mut lookups = terms.iter().map(|t| {
+            self.search_index.candidates(t).map(|mut hits| {
+                hits.extend(unindexed.clone());
+                hits
+            })
+        });
+        let candidates = match self.filters.keyword_mode {
+            KeywordMode::All => lookups.flatten().reduce(|a, b| a.intersection(&b).copied().collect()),
+            KeywordMode::Any if terms.is_empty() => None,
+            KeywordMode::Any => lookups.try_fold(HashSet::new(), |mut acc, hits| {
+                acc.extend(hits?);
+                Some(acc)
+            }),
+        };
+        let mut evs: Vec<EventRecord> = match candidates {
+            Some(candidates) => {
+                let mut indices: Vec<usize> = candidates.into_iter().collect();
+                indices.sort_unstable();
+                indices.into_iter().map(|i| self.all_events[i].clone()).collect()
+            }
+            None => self.all_events.clone(),
+        };
+        let keyword_match = |e: &EventRecord| {
+            let scope = &self.filters.keyword_scope;
+            let has = |i: usize| {
+                let found = |text: &str| match word_patterns.get(i).and_then(Option::as_ref) {
+                    Some(pattern) => pattern.is_match(text),
+                    None => has_text(text, &terms[i]),
+                };
+                (scope.description && found(&e.description))
+                    || (scope.raw_xml && found(&e.raw_xml))
+                    || (scope.source && found(&e.source))
+                    || (scope.user && found(&e.user))
+            };
+            match self.filters.keyword_mode {
+                KeywordMode::All => (0..terms.len()).all(has),
+                KeywordMode::Any => terms.is_empty() || (0..terms.len()).any(has),
+            }
+        };
+        let column_filters: Vec<_> = if self.show_column_filters {
+            self.visible_columns().into_iter()
+                .filter_map(|c| self.filters.columns.get(&c).filter(|t| !t.is_empty()).map(|t| (c, fold(t))))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        // basic filters
+        evs.retain(|e| {
+            (self.filters.levels.is_empty() || self.filters.levels.contains(&e.level)) &&
+            (source.is_empty() || has_text(&e.source, &source)) &&
+            (self.filters.event_ids.is_empty() || self.filters.event_ids.contains(&e.event_id)) &&
+            (self.filters.task.is_none_or(|task| e.task == task)) &&
+            (user.is_empty() || has_text(&e.user, &user)) &&
+            (self.filters.computers.is_empty() || self.filters.computers.contains(&e.computer)) &&
+            keyword_match(e) &&
+            self.filters.audit.matches(e) &&
+            (self.filters.date_from.is_none_or(|d| e.time_created.date_naive() >= d)) &&
+            (self.filters.date_to.is_none_or(|d| e.time_created.date_naive() <= d)) &&
+            in_time_window(e.time_created.time(), self.filters.time_from, self.filters.time_to) &&
+            !(self.filters.hide_empty_descriptions && e.description.trim().is_empty()) &&
+            !(self.filters.new_since_baseline && self.baseline.as_ref().is_some_and(|b| b.keys.contains(&event_key(e)))) &&
+            column_filters.iter().all(|(column, text)| has_text(&column.text(e, &self.time_format), text))
+        });
+        evs.sort_by(|a, b| {
+            let ord = match self.sort_by {
+                // Full precision, so events from different logs within one second interleave correctly;
+                // RecordID orders events a channel wrote with the same timestamp
+                SortBy::Time => (a.time_created, &a.log_name, a.record_id).cmp(&(b.time_created, &b.log_name, b.record_id)),
+                SortBy::Level => level_severity(&a.level).cmp(&level_severity(&b.level)),
+                SortBy::EventID => a.event_id.cmp(&b.event_id),
+                SortBy::Source => a.source.cmp(&b.source),
+            };
+            let ord = if self.sort_desc { ord.reverse() } else { ord };
+            // Ties always fall back to most recent first, then highest RecordID, so the order never
+            // depends on the order events arrived in
+            ord.then_with(|| (b.time_created, &b.log_name, b.record_id).cmp(&(a.time_created, &a.log_name, a.record_id)))
+        });
+        // Follow the selected event to its new position rather than keeping the index
+        if let Some(prev) = self.selected.and_then(|i| self.filtered_events.get(i)) {
+            self.selected = evs.iter().position(|e| e.same_event(prev));
+        }
+        self.filtered_events = evs;
+        self.rebuild_view_rows();
+    }
+
+    /// Maps filtered_events to table rows, folding runs of consecutive duplicates
+    /// into their first event unless that run has been expanded. When grouping by
+    /// day, each run of events on the same date gets a heading row, and the events
+    /// only follow it once that day is expanded.
+    fn rebuild_view_rows(&mut self) {
+        self.view_rows.clear();
+        let (mut i, end) = match self.view_limit {
+            ViewLimit::All => (0, self.filtered_events.len()),
+            ViewLimit::First(n) => (0, n.min(self.filtered_events.len())),
+            ViewLimit::Last(n) => (self.filtered_events.len().saturating_sub(n), self.filtered_events.len()),
+        };
+        // Reaching day_end starts the next day's heading; never reached when not grouping
+        let mut day_end = if self.group_by_day { i } else { end };
+        while i < end {
+            if i == day_end {
+                let date = self.filtered_events[i].time_created.date_naive();
+                day_end = i + self.filtered_events[i..end].iter().take_while(|e| e.time_created.date_naive() == date).count();
+                let expanded = self.expanded_days.contains(&date);
+                self.view_rows.push(ViewRow { index: i, run: 1, expanded, day: Some(day_end - i) });
+                if !expanded {
+                    i = day_end;
+                    continue;
+                }
+            }
+            let mut run = 1;
+            if self.collapse_duplicates {
+                let head = &self.filtered_events[i];
+                while i + run < day_end && is_duplicate(head, &self.filtered_events[i + run]) {
+                    run += 1;
+                }
+            }
+            let expanded = run > 1 && self.expanded_runs.contains(&run_key(&self.filtered_events[i]));
+            self.view_rows.push(ViewRow { index: i, run, expanded, day: None });
+            if expanded {
+                self.view_rows.extend((i + 1..i + run).map(|index| ViewRow { index, run: 1, expanded: false, day: None }));
+            }
+            i += run;
+        }
+    }
+
+    /// Table row showing filtered_events[idx], which may be the head of a collapsed run,
+    /// or the heading of its day when that day is collapsed
+    fn view_row_of(&self, idx: usize) -> Option<usize> {
+        let event_row = self.view_rows.iter().position(|r| {
+            let span = if r.expanded { 1 } else { r.run };
+            r.day.is_none() && (r.index..r.index + span).contains(&idx)
+        });
+        event_row.or_else(|| self.view_rows.iter().position(|r| r.day.is_some_and(|n| (r.index..r.index + n).contains(&idx))))
+    }
+
+    /// Selects and scrolls to the oldest event at or after `target`
+    fn jump_to_time(&mut self, target: DateTime<Local>) {
+        let target = target.timestamp();
+        let found = if self.sort_by == SortBy::Time {
+            if self.sort_desc {
+                // Newest first: events at or after the target form the prefix
+                self.filtered_events.partition_point(|e| e.time_created.timestamp() >= target).checked_sub(1)
+            } else {
+                let idx = self.filtered_events.partition_point(|e| e.time_created.timestamp() < target);
+                (idx < self.filtered_events.len()).then_some(idx)
+            }
+        } else {
+            self.filtered_events.iter().enumerate()
+                .filter(|(_, e)| e.time_created.timestamp() >= target)
+                .min_by_key(|(_, e)| e.time_created.timestamp())
+                .map(|(i, _)| i)
+        };
+        if let Some(idx) = found {
+            self.selected = Some(idx);
+            self.scroll_to_row = self.view_row_of(idx).map(|row| (row, egui::Align::Center));
+        }
+    }
+
+    fn log_view_key(&self) -> Vec<String> {
+        let mut key = self.selected_logs.clone();
+        key.sort();
+        key
+    }
+
+    /// Remembers the current scroll position and selection under `key`
+    fn save_log_view(&mut self, key: Vec<String>) {
+        let top = self.first_visible_row
+            .and_then(|row| self.view_rows.get(row))
+            .and_then(|r| self.filtered_events.get(r.index))
+            .cloned();
+        let selected = self.selected.and_then(|i| self.filtered_events.get(i)).cloned();
+        self.log_views.insert(key, LogViewState { top, selected });
+    }
+
+    /// Puts back the scroll position and selection last seen for the selected logs
+    fn restore_log_view(&mut self) {
+        let Some(state) = self.log_views.get(&self.log_view_key()) else {
+            self.selected = None;
+            self.scroll_to_row = Some((0, egui::Align::TOP));
+            return;
+        };
+        let find = |target: &Option<EventRecord>| {
+            target.as_ref().and_then(|t| self.filtered_events.iter().position(|e| e.same_event(t)))
+        };
+        let selected = find(&state.selected);
+        let top = find(&state.top).and_then(|idx| self.view_row_of(idx));
+        self.selected = selected;
+        self.scroll_to_row = Some((top.unwrap_or(0), egui::Align::TOP));
+    }
+
+    /// Whether keys typed now continue the current type-ahead search
+    fn type_ahead_active(&self) -> bool {
+        !self.type_ahead.is_empty() && self.type_ahead_at.is_some_and(|at| at.elapsed() < TYPE_AHEAD_IDLE)
+    }
+
+    /// Adds `text` to the type-ahead search and selects the nearest row whose description
+    /// or source contains it, starting after the selection when a new search begins
+    fn type_ahead(&mut self, text: &str) {
+        let continuing = self.type_ahead_active();
+        if !continuing {
+            self.type_ahead.clear();
+        }
+        self.type_ahead.push_str(text);
+        if self.type_ahead.trim().is_empty() {
+            self.type_ahead.clear();
+            return;
+        }
+        self.type_ahead_at = Some(Instant::now());
+        let needle = self.type_ahead.to_lowercase();
+        let len = self.filtered_events.len();
+        let start = match self.selected {
+            Some(idx) if continuing => idx,
+            Some(idx) => idx + 1,
+            None => 0,
+        };
+        let found = (0..len).map(|i| (start + i) % len).find(|&i| type_ahead_match(&self.filtered_events[i], &needle));
+        if let Some(idx) = found {
+            self.follow = false;
+            self.selected = Some(idx);
+            self.scroll_to_row = self.view_row_of(idx).map(|row| (row, egui::Align::Center));
+        }
+    }
+
+    /// Moves the selection `delta` events through filtered_events and scrolls the table to it
+    fn select_relative(&mut self, delta: isize) {
+        let Some(last) = self.filtered_events.len().checked_sub(1) else {
+            return;
+        };
+        let current = self.selected.unwrap_or(0);
+        let idx = current.saturating_add_signed(delta).min(last);
+        self.follow = false;
+        self.selected = Some(idx);
+        self.scroll_to_row = self.view_row_of(idx).map(|row| (row, egui::Align::Center));
+    }
+
+    /// The filtered events as they should be written out, pseudonymized when anonymizing
+    fn export_events(&mut self) -> Vec<EventRecord> {
+        if self.anonymize_exports {
+            self.anonymizer.anonymize(&self.filtered_events)
+        } else {
+            self.filtered_events.clone()
+        }
+    }
+
+    /// Deletes the cached import; don't leave a copy of the evidence behind once caching is off
+    fn forget_import_cache(&mut self) {
+        if let Some(path) = import_cache_path() {
+            let _ = std::fs::remove_file(path);
+        }
+        self.settings.last_import_sources.clear();
+        self.restore_offer = false;
+    }
+
+    /// Passes the settings that take effect outside the app, in the poller and in
+    /// event parsing and queries, to where they are used, and checks the time format
+    fn apply_settings(&mut self) {
+        self.time_format = checked_time_format(&self.settings.time_format);
+        self.live_min_severity.store(self.settings.live_min_severity, Ordering::Relaxed);
+        set_description_format(&self.settings.description_separator, self.settings.description_names);
+        set_rendered_xml(self.settings.rendered_xml);
+        set_query_timeout(self.settings.query_timeout_secs);
+    }
+
+    /// Back to a fresh install: default settings, theme and filters, and egui's remembered
+    /// layout (panel and column widths) forgotten; the next save overwrites the stored state
+    fn reset_settings(&mut self, ctx: &egui::Context) {
+        self.settings = Settings::default();
+        self.forget_import_cache();
+        self.apply_settings();
+        self.theme_mode = ThemeMode::System;
+        ctx.set_style(egui::Style::default());
+        ctx.memory_mut(|m| *m = Default::default());
+        self.filters = Filters::default();
+        self.event_id_input.clear();
+        self.time_from_input.clear();
+        self.time_to_input.clear();
+        self.task_input.clear();
+        self.data_columns.clear();
+        self.show_column_filters = false;
+        self.collapse_duplicates = false;
+        self.group_by_day = false;
+        self.view_limit = ViewLimit::All;
+        self.refresh_page();
+        self.status = "Settings reset to defaults".to_string();
+    }
+
+    /// A `Get-WinEvent` command reproducing the current source and filters. What the
+    /// FilterHashtable can't express is checked in a Where-Object block instead.
+    fn powershell_command(&self) -> String {
+        let quote = |s: &str| format!("'{}'", s.replace('\'', "''"));
+        let contains = |field: &str, text: &str| if self.filters.match_case {
+            format!("({}).Contains({})", field, quote(text))
+        } else {
+            format!("({}).IndexOf({}, [StringComparison]::OrdinalIgnoreCase) -ge 0", field, quote(text))
+        };
+        let mut table: Vec<String> = Vec::new();
+        let mut checks: Vec<String> = Vec::new();
+        let mut command = String::from("Get-WinEvent");
+        match &self.file_source {
+            Some(FileSource::Evtx(path)) => table.push(format!("Path = {}", quote(&path.to_string_lossy()))),
+            Some(FileSource::Query(path)) => {
+                command.push_str(&format!(" -FilterXml ([xml](Get-Content -Raw {}))", quote(&path.to_string_lossy())));
+            }
+            // Not an event log, so the closest equivalent is following the file
+            Some(FileSource::Watch(path)) => return format!("Get-Content -Path {} -Wait", quote(&path.to_string_lossy())),
+            None => table.push(format!("LogName = {}", self.selected_logs.iter().map(|l| quote(l)).collect::<Vec<_>>().join(","))),
+        }
+        let levels: Vec<&str> = self.filters.levels.iter().map(String::as_str).collect();
+        let level_nums: Vec<&str> = levels.iter().filter_map(|l| match *l {
+            "Critical" => Some("1"),
+            "Error" => Some("2"),
+            "Warning" => Some("3"),
+            "Information" => Some("4"),
+            "Verbose" => Some("5"),
+            _ => None,
+        }).collect();
+        let audit_bits: u64 = levels.iter().map(|l| match *l {
+            "Audit Failure" => KEYWORD_AUDIT_FAILURE,
+            "Audit Success" => KEYWORD_AUDIT_SUCCESS,
+            _ => 0,
+        }).sum();
+        if audit_bits == 0 && !level_nums.is_empty() {
+            table.push(format!("Level = {}", level_nums.join(",")));
+        } else if audit_bits != 0 && level_nums.is_empty() {
+            table.push(format!("Keywords = {}", audit_bits));
+        } else if audit_bits != 0 {
+            // Level and Keywords would both have to match, so mixed selections are checked per event
+            checks.push(format!("($_.Level -in {} -or ($_.Keywords -band {}))", level_nums.join(","), audit_bits));
+        }
+        if self.filters.audit != AuditFilter::All {
+            checks.push(format!("($_.Keywords -band {})", self.filters.audit.keyword_bits()));
+        }
+        if !self.filters.source.is_empty() {
+            table.push(format!("ProviderName = {}", quote(&format!("*{}*", self.filters.source))));
+        }
+        let mut ids: Vec<u16> = self.filters.event_ids.iter().copied().collect();
+        ids.sort_unstable();
+        // The FilterHashtable only takes up to 23 IDs; longer sets become ranges in Where-Object
+        if ids.len() <= 23 && !ids.is_empty() {
+            table.push(format!("Id = {}", ids.iter().map(u16::to_string).collect::<Vec<_>>().join(",")));
+        } else if !ids.is_empty() {
+            let mut runs: Vec<(u16, u16)> = Vec::new();
+            for id in ids {
+                match runs.last_mut() {
+                    Some((_, end)) if *end + 1 == id => *end = id,
+                    _ => runs.push((id, id)),
+                }
+            }
+            let parts: Vec<String> = runs.iter().map(|(a, b)| if a == b { format!("@({})", a) } else { format!("@({}..{})", a, b) }).collect();
+            checks.push(format!("$_.Id -in ({})", parts.join(" + ")));
+        }
+        if let Some(from) = self.filters.date_from {
+            table.push(format!("StartTime = [datetime]{}", quote(&from.format("%Y-%m-%d").to_string())));
+        }
+        if let Some(to) = self.filters.date_to.and_then(|d| d.succ_opt()) {
+            table.push(format!("EndTime = [datetime]{}", quote(&to.format("%Y-%m-%d").to_string())));
+        }
+        if let Some(task) = self.filters.task {
+            checks.push(format!("$_.Task -eq {}", task));
+        }
+        if !self.filters.computers.is_empty() {
+            let hosts: Vec<String> = self.filters.computers.iter().map(|c| quote(c)).collect();
+            checks.push(format!("$_.MachineName -in {}", hosts.join(",")));
+        }
+        let scope = &self.filters.keyword_scope;
+        let term_checks: Vec<String> = keyword_terms(&self.filters.keyword).iter().map(|term| {
+            let mut fields = Vec::new();
+            if scope.description {
+                fields.push("[string]$_.Message");
+            }
+            if scope.raw_xml {
+                fields.push("$_.ToXml()");
+            }
+            if scope.source {
+                fields.push("[string]$_.ProviderName");
+            }
+            if scope.user {
+                fields.push("[string]$_.UserId");
+            }
+            if fields.is_empty() {
+                return "$false".to_string();
+            }
+            let any: Vec<String> = fields.iter().map(|f| match (self.filters.whole_word, self.filters.match_case) {
+                (true, true) => format!("{} -cmatch {}", f, quote(&whole_word_pattern(term))),
+                (true, false) => format!("{} -match {}", f, quote(&whole_word_pattern(term))),
+                (false, _) => contains(f, term),
+            }).collect();
+            format!("({})", any.join(" -or "))
+        }).collect();
+        if !term_checks.is_empty() {
+            let join = match self.filters.keyword_mode {
+                KeywordMode::All => " -and ",
+                KeywordMode::Any => " -or ",
+            };
+            checks.push(format!("({})", term_checks.join(join)));
+        }
+        if !self.filters.user.is_empty() {
+            checks.push(contains("[string]$_.UserId", &self.filters.user));
+        }
+        if self.filters.time_from.is_some() || self.filters.time_to.is_some() {
+            let span = |t: NaiveTime| format!("[timespan]'{}'", t.format("%H:%M:%S"));
+            let from = self.filters.time_from.map(|t| format!("$_.TimeCreated.TimeOfDay -ge {}", span(t)));
+            let to = self.filters.time_to.map(|t| format!("$_.TimeCreated.TimeOfDay -le {}", span(t)));
+            let wraps = matches!((self.filters.time_from, self.filters.time_to), (Some(a), Some(b)) if a > b);
+            let join = if wraps { " -or " } else { " -and " };
+            checks.push(format!("({})", from.into_iter().chain(to).collect::<Vec<_>>().join(join)));
+        }
+        if self.filters.hide_empty_descriptions {
+            checks.push("-not [string]::IsNullOrWhiteSpace($_.Message)".to_string());
+        }
+        if !table.is_empty() {
+            command.push_str(&format!(" -FilterHashtable @{{ {} }}", table.join("; ")));
+        }
+        if !checks.is_empty() {
+            command.push_str(&format!(" | Where-Object {{ {} }}", checks.join(" -and ")));
+        }
+        command
+    }
+
+    /// Asks for a path and writes the filtered events there as CSV, in table order
+    fn save_csv(&mut self) {
+        let Some(path) = rfd::FileDialog::new().add_filter("CSV", &["csv"]).set_file_name("events.csv").save_file() else {
+            return;
+        };
+        let mut columns = self.visible_columns();
+        let events = self.export_events();
+        if self.export_data_fields {
+            let extra: Vec<TableColumn> = export::data_keys(&events).into_iter()
+                .map(TableColumn::Data)
+                .filter(|c| !columns.contains(c))
+                .collect();
+            columns.extend(extra);
+        }
+        let header: Vec<&str> = columns.iter().map(TableColumn::title).collect();
+        let rows = events.iter().enumerate().map(|(i, ev)| {
+            let prev = i.checked_sub(1).map(|p| &events[p]);
+            columns.iter().map(|c| match c {
+                TableColumn::Delta => delta_text(ev, prev),
+                c => c.text(ev, &self.time_format),
+            }).collect::<Vec<_>>()
+        });
+        self.status = match export::rows_to_csv(&header, rows, &path) {
+            Ok(()) => format!("Saved {} events to {}", format_count(events.len()), path.display()),
+            Err(e) => format!("Export failed: {}", e),
+        };
+    }
+
+    /// Back to the default columns at their initial widths
+    fn reset_columns(&mut self) {
+        let defaults = Settings::default();
+        self.settings.show_task_column = defaults.show_task_column;
+        self.settings.show_delta_column = defaults.show_delta_column;
+        self.settings.column_layout = self.settings.column_layout.wrapping_add(1);
+        self.data_columns.clear();
+        // Filters typed under removed columns stop applying
+        self.apply_filters();
+    }
+
+    /// Columns currently shown in the table, left to right
+    fn visible_columns(&self) -> Vec<TableColumn> {
+        let mut columns = vec![TableColumn::Time];
+        if self.settings.show_delta_column {
+            columns.push(TableColumn::Delta);
+        }
+        columns.extend([
+            TableColumn::Log,
+            TableColumn::Level,
+            TableColumn::EventId,
+            TableColumn::Source,
+            TableColumn::User,
+            TableColumn::Computer,
+        ]);
+        if self.settings.show_task_column {
+            columns.push(TableColumn::Task);
+        }
+        columns.extend(self.data_columns.iter().cloned().map(TableColumn::Data));
+        columns
+    }
+
+    /// Header label that sorts by `column`, flipping direction when it is already active
+    fn sort_header(&mut self, ui: &mut egui::Ui, label: &str, column: SortBy) {
+        let active = self.sort_by == column;
+        let text = match (active, self.sort_desc) {
+            (true, true) => format!("{} ⏷", label),
+            (true, false) => format!("{} ⏶", label),
+            (false, _) => label.to_string(),
+        };
+        if ui.selectable_label(active, text).clicked() {
+            if active {
+                self.sort_desc = !self.sort_desc;
+            } else {
+                self.sort_by = column;
+                self.sort_desc = true;
+            }
+            self.apply_filters();
+        }
+    }
+
+    fn update_live(&mut self) {
+        while self.live_arrivals.front().is_some_and(|at| at.elapsed() > RATE_WINDOW) {
+            self.live_arrivals.pop_front();
+        }
+        // Drained even while paused so the channel doesn't back up and flood in on resume
+        let mut incoming = Vec::new();
+        while let Ok(msg) = self.recv.try_recv() {
+            match msg {
+                // Live events don't belong in an opened file or query
+                PollMessage::Event(_) if self.file_source.is_some() => {}
+                PollMessage::Event(ev) => incoming.push(*ev),
+                PollMessage::Error(e) => self.poll_error = Some(e),
+                PollMessage::Heartbeat => {
+                    self.poll_error = None;
+                    self.last_poll = Some(Local::now());
+                }
+            }
+        }
+        if let Some(watch) = &self.file_watch {
+            incoming.extend(std::iter::from_fn(|| watch.try_recv()));
+        }
+        // The poller re-sends the newest events every round
+        incoming.retain(|ev| self.seen_events.insert(event_key(ev)));
+        if !self.settings.keep_raw_xml {
+            for ev in &mut incoming {
+                ev.raw_xml = String::new();
+            }
+        }
+        if self.paused {
+            self.paused_buffer.extend(incoming);
+            while self.paused_buffer.len() > PAUSE_BUFFER_MAX {
+                self.paused_buffer.pop_front();
+                self.paused_dropped += 1;
+            }
+            return;
+        }
+        let mut received = false;
+        let mut alert: Option<String> = None;
+        self.paused_dropped = 0;
+        let buffered = std::mem::take(&mut self.paused_buffer);
+        for ev in buffered.into_iter().chain(incoming) {
+            self.live_arrivals.push_back(Instant::now());
+            let wanted = match ev.level.as_str() {
+                "Critical" => self.settings.sound_on_critical,
+                "Error" => self.settings.sound_on_error,
+                _ => false,
+            };
+            if wanted && alert.as_ref().is_none_or(|a| level_severity(&ev.level) > level_severity(a)) {
+                alert = Some(ev.level.clone());
+            }
+            // Appended rather than prepended so the search index stays valid;
+            // apply_filters does the ordering
+            self.all_events.push(ev);
+            received = true;
+        }
+        if received {
+            self.apply_filters();
+            if self.follow {
+                let newest = self.filtered_events.iter().enumerate().max_by_key(|(_, e)| e.time_created).map(|(i, _)| i);
+                if let Some(idx) = newest {
+                    self.selected = Some(idx);
+                    self.scroll_to_row = self.view_row_of(idx).map(|row| (row, egui::Align::Center));
+                }
+            }
+        }
+        if let Some(level) = alert {
+            let cooled_down = self.last_sound.is_none_or(|at| at.elapsed() >= SOUND_COOLDOWN);
+            if !self.settings.sound_muted && cooled_down {
+                sound::play_alert(&level);
+                self.last_sound = Some(Instant::now());
+            }
+        }
+    }
+
+    fn run_xml_search(&mut self) {
+        self.xml_search_hits.clear();
+        if self.xml_search.is_empty() {
+            return;
+        }
+        for ev in &self.all_events {
+            if let Some(snippet) = snippet_around(&ev.raw_xml, &self.xml_search, 60) {
+                self.xml_search_hits.push((ev.clone(), snippet));
+                if self.xml_search_hits.len() >= XML_SEARCH_MAX_HITS {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Overview strip of every table row, one pixel line per bucket of rows colored by its
+    /// most severe Warning-or-worse event; clicking or dragging scrolls the table there
+    fn minimap(&mut self, ui: &mut egui::Ui) {
+        let (rect, response) = ui.allocate_exact_size(ui.available_size(), egui::Sense::click_and_drag());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, ui.visuals().faint_bg_color);
+        let rows = self.view_rows.len();
+        let buckets = rect.height().max(1.0) as usize;
+        let mut worst: Vec<Option<(u8, &str)>> = vec![None; buckets];
+        for (row, view_row) in self.view_rows.iter().enumerate() {
+            let Some(ev) = self.filtered_events.get(view_row.index) else {
+                continue;
+            };
+            let severity = level_severity(&ev.level);
+            if severity < 3 {
+                continue;
+            }
+            // With fewer rows than pixels a row spans several lines
+            let start = row * buckets / rows;
+            let end = ((row + 1) * buckets / rows).max(start + 1);
+            for bucket in &mut worst[start..end] {
+                if bucket.is_none_or(|(s, _)| severity > s) {
+                    *bucket = Some((severity, ev.level.as_str()));
+                }
+            }
+        }
+        for (i, bucket) in worst.iter().enumerate() {
+            if let Some((_, level)) = bucket {
+                let y = rect.top() + i as f32;
+                painter.hline(rect.x_range(), y, egui::Stroke::new(1.0, level_color(level, ui.visuals())));
+            }
+        }
+        if let Some(first) = self.first_visible_row {
+            let y = rect.top() + first as f32 * rect.height() / rows as f32;
+            painter.hline(rect.x_range(), y, egui::Stroke::new(2.0, ui.visuals().selection.bg_fill));
+        }
+        if let Some(pos) = response.interact_pointer_pos() {
+            let frac = ((pos.y - rect.top()) / rect.height()).clamp(0.0, 1.0);
+            let row = ((frac * rows as f32) as usize).min(rows - 1);
+            self.scroll_to_row = Some((row, egui::Align::Center));
+        }
+        response.on_hover_text("Warnings and errors across the whole list; click to jump");
+    }
+
+    /// Asks for one or more event files and imports them
+    fn pick_and_import(&mut self) {
+        if let Some(paths) = rfd::FileDialog::new().add_filter("Event Files", &["evtx", "xml", "csv", "txt", "log"]).pick_files() {
+            self.import_files(&paths);
+            if !self.filtered_events.is_empty() {
+                self.selected = Some(0);
+            }
+        }
+    }
+
+    /// Asks for an .evtx snapshot and makes it the baseline, turning on the "new since" filter
+    fn pick_baseline(&mut self) {
+        let Some(path) = rfd::FileDialog::new().add_filter("Event Log", &["evtx"]).pick_file() else {
+            return;
+        };
+        match read_evtx(&path) {
+            Ok((events, _)) => {
+                let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                self.set_baseline(name, events.iter().map(event_key).collect());
+            }
+            Err(e) => self.status = e,
+        }
+    }
+
+    fn set_baseline(&mut self, name: String, keys: HashSet<u64>) {
+        self.status = format!("Baseline {} holds {} events", name, format_count(keys.len()));
+        self.baseline = Some(Baseline { name, keys });
+        self.filters.new_since_baseline = true;
+        self.apply_filters();
+    }
+
+    /// Asks for a directory and imports every .evtx file found under it
+    fn pick_and_import_folder(&mut self) {
+        let Some(dir) = rfd::FileDialog::new().pick_folder() else {
+            return;
+        };
+        let mut paths = Vec::new();
+        find_evtx_files(&dir, &mut paths);
+        if paths.is_empty() {
+            self.status = format!("No .evtx files found under {}", dir.display());
+            return;
+        }
+        paths.sort();
+        self.import_paths(&paths, self.settings.folder_host_from_parent);
+        if !self.filtered_events.is_empty() {
+            self.selected = Some(0);
+        }
+    }
+
+    /// Imports every file in `paths` as one combined set replacing the current events,
+    /// each event's log_name set to the file it came from
+    pub fn import_files(&mut self, paths: &[PathBuf]) {
+        self.import_paths(paths, false);
+    }
+
+    /// import_files that, with `host_from_folder`, also sets each event's computer to
+    /// the name of the folder holding its file, as in per-host log collections
+    fn import_paths(&mut self, paths: &[PathBuf], host_from_folder: bool) {
+        self.paused = true; // Pause polling when importing
+        let mut events = Vec::new();
+        let mut skipped = 0;
+        let mut problems = Vec::new();
+        for path in paths {
+            let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            match self.read_import(path, &name) {
+                Ok((mut imported, file_skipped, xml_error)) => {
+                    let host = path.parent().and_then(|p| p.file_name()).map(|n| n.to_string_lossy().to_string());
+                    if let Some(host) = host.filter(|_| host_from_folder) {
+                        imported.iter_mut().for_each(|e| e.computer = host.clone());
+                    }
+                    events.extend(imported);
+                    skipped += file_skipped;
+                    if let Some(e) = xml_error {
+                        problems.push(format!("{} stopped at XML error: {}", name, e));
+                    }
+                }
+                Err(e) => problems.push(format!("{}: {}", name, e)),
+            }
+        }
+        if !self.settings.keep_raw_xml {
+            events.iter_mut().for_each(|e| e.raw_xml = String::new());
+        }
+        self.all_events = events;
+        self.seen_events = self.all_events.iter().map(event_key).collect();
+        self.reset_search_index();
+        self.selected = None;
+        self.apply_filters();
+        if self.settings.cache_imports {
+            self.settings.last_import_sources = paths.iter().map(|p| p.display().to_string()).collect();
+            save_import_cache(self.all_events.clone());
+        }
+        self.restore_offer = false;
+        let mut summary = format!("Imported {} events", format_count(self.all_events.len()));
+        if paths.len() > 1 {
+            summary.push_str(&format!(" from {} files", paths.len()));
+        }
+        if skipped > 0 {
+            summary.push_str(&format!(" ({} skipped)", format_count(skipped)));
+        }
+        if !problems.is_empty() {
+            summary.push_str(&format!("; {}", problems.join("; ")));
+        }
+        self.status = summary;
+    }
+
+    /// Loads the events cached by the last import in place of the current ones
+    fn restore_last_import(&mut self) {
+        self.restore_offer = false;
+        let loaded = import_cache_path()
+            .ok_or_else(|| "no storage folder".to_string())
+            .and_then(|path| File::open(path).map_err(|e| e.to_string()))
+            .and_then(|file| serde_json::from_reader::<_, Vec<EventRecord>>(std::io::BufReader::new(file)).map_err(|e| e.to_string()));
+        match loaded {
+            Ok(events) => {
+                self.paused = true; // As after the original import
+                self.file_source = None;
+                self.all_events = events;
+                self.seen_events = self.all_events.iter().map(event_key).collect();
+                self.reset_search_index();
+                self.selected = None;
+                self.apply_filters();
+                self.status = format!("Restored {} events from the last import", format_count(self.all_events.len()));
+            }
+            Err(e) => self.status = format!("Cannot restore the last import: {}", e),
+        }
+    }
+
+    /// Parses one imported file into events tagged with `log_name`, returning them with
+    /// the number of unreadable records and the XML error that cut the file short, if any
+    fn read_import(&self, path: &Path, log_name: &str) -> Result<(Vec<EventRecord>, usize, Option<String>), String> {
+        let mut events = Vec::new();
+        let mut skipped = 0;
+        let mut xml_error = None;
+        let extension = path.extension().map(|e| e.to_string_lossy().to_ascii_lowercase()).unwrap_or_default();
+        if extension == "evtx" {
+            (events, skipped) = read_evtx(path)?;
+            events.iter_mut().for_each(|e| e.log_name = log_name.to_string());
+        } else if extension == "xml" {
+            let mut contents = String::new();
+            File::open(path).and_then(|mut file| file.read_to_string(&mut contents)).map_err(|e| e.to_string())?;
+            (events, xml_error) = parse_events_xml(&contents, log_name);
+            events.iter_mut().for_each(|e| e.log_name = log_name.to_string());
+        } else if extension == "csv" {
+            let file = File::open(path).map_err(|e| e.to_string())?;
+            let mut rdr = ReaderBuilder::new().has_headers(true).from_reader(file);
+            for record in rdr.records() {
+                let record = match record {
+                    Ok(record) => record,
+                    Err(_) => {
+                        skipped += 1;
+                        continue;
+                    }
+                };
+                let description = record.iter().collect::<Vec<_>>().join(", ");
+                events.push(EventRecord {
+                    log_name: log_name.to_string(),
+                    level: "Info".to_string(),
+                    source: "Import".to_string(),
+                    description: description.chars().take(200).collect(),
+                    raw_xml: description,
+                    ..Default::default()
+                });
+            }
+        } else if extension == "txt" || extension == "log" {
+            let pattern = regex::Regex::new(&self.settings.text_import_pattern)
+                .map_err(|e| format!("invalid line pattern: {}", e))?;
+            let mut contents = String::new();
+            File::open(path).and_then(|mut file| file.read_to_string(&mut contents)).map_err(|e| e.to_string())?;
+            events = parse_text_log(&contents, &pattern, log_name);
+        } else {
+            return Err("unsupported file type".to_string());
+        }
+        Ok((events, skipped, xml_error))
+    }
+}
+
+impl App for EventViewerApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, SETTINGS_KEY, &self.settings);
+    }
+
+    fn update(&mut self, ctx: &egui::Context, frame: &mut Frame) {
+        match self.theme_mode {
+            ThemeMode::System => {}, // Use default
+            ThemeMode::GruvboxDark => {
+                ctx.set_visuals(egui::Visuals::dark());
+                ctx.set_style(egui::Style {
+                    visuals: egui::Visuals {
+                        window_fill: egui::Color32::from_rgb(40, 40, 40),
+                        panel_fill: egui::Color32::from_rgb(29, 32, 33),
+                        faint_bg_color: egui::Color32::from_rgb(60, 56, 54),
+                        extreme_bg_color: egui::Color32::from_rgb(29, 32, 33),
+                        ..egui::Visuals::dark()
+                    },
+                    ..egui::Style::default()
+                });
+            },
+            ThemeMode::GruvboxLight => {
+                ctx.set_visuals(egui::Visuals::light());
+                ctx.set_style(egui::Style {
+                    visuals: egui::Visuals {
+                        window_fill: egui::Color32::from_rgb(251, 241, 199),
+                        panel_fill: egui::Color32::from_rgb(235, 219, 178),
+                        faint_bg_color: egui::Color32::from_rgb(213, 196, 161),
+                        extreme_bg_color: egui::Color32::from_rgb(235, 219, 178),
+                        ..egui::Visuals::light()
+                    },
+                    ..egui::Style::default()
+                });
+            },
+            ThemeMode::SolarizedDark => {
+                ctx.set_visuals(egui::Visuals::dark());
+                ctx.set_style(egui::Style {
+                    visuals: egui::Visuals {
+                        window_fill: egui::Color32::from_rgb(0, 43, 54),
+                        panel_fill: egui::Color32::from_rgb(7, 54, 66),
+                        faint_bg_color: egui::Color32::from_rgb(88, 110, 117),
+                        extreme_bg_color: egui::Color32::from_rgb(7, 54, 66),
+                        ..egui::Visuals::dark()
+                    },
+                    ..egui::Style::default()
+                });
             },
             ThemeMode::SolarizedLight => {
                 ctx.set_visuals(egui::Visuals::light());
@@ -408,32 +1953,359 @@ impl App for EventViewerApp {
         }
 
         self.update_live();
+        self.update_loads();
+        if self.background_busy() {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+        let keyword_id = egui::Id::new("keyword_filter");
+        let typing = ctx.memory(|m| m.focus().is_some());
+        let finding = self.type_ahead_active();
+        let (toggle_pause, refresh, find, export, toggle_details, typed) = ctx.input_mut(|i| {
+            let ctrl_space = i.consume_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Space));
+            // Bare Space only when it isn't going into a text field or a type-ahead search
+            let space = !typing && !finding && i.consume_key(egui::Modifiers::NONE, egui::Key::Space);
+            let typed: String = if typing || space {
+                String::new()
+            } else {
+                i.events.iter().filter_map(|e| match e {
+                    egui::Event::Text(text) => Some(text.as_str()),
+                    _ => None,
+                }).collect()
+            };
+            (
+                ctrl_space || space,
+                i.consume_key(egui::Modifiers::NONE, egui::Key::F5),
+                i.consume_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::F)),
+                i.consume_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::E)),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::F9),
+                typed,
+            )
+        });
+        if !typed.is_empty() {
+            self.type_ahead(&typed);
+        }
+        if self.type_ahead_active() {
+            // Repaint once idle so the search indicator goes away
+            ctx.request_repaint_after(TYPE_AHEAD_IDLE);
+        }
+        if toggle_pause {
+            self.paused = !self.paused;
+        }
+        if refresh {
+            self.refresh_page();
+        }
+        if find {
+            ctx.memory_mut(|m| m.request_focus(keyword_id));
+        }
+        if export {
+            self.save_csv();
+        }
+        if toggle_details {
+            self.settings.show_details = !self.settings.show_details;
+        }
+        let (prev, next) = ctx.input(|i| {
+            (i.modifiers.alt && i.key_pressed(egui::Key::ArrowLeft), i.modifiers.alt && i.key_pressed(egui::Key::ArrowRight))
+        });
+        if prev {
+            self.select_relative(-1);
+        } else if next {
+            self.select_relative(1);
+        }
+        let title = format!(
+            "Event Viewer — {} ({} events)",
+            match &self.file_source {
+                Some(source) => source.path().display().to_string(),
+                None if self.selected_logs.is_empty() => "no logs".to_string(),
+                None => self.selected_logs.join(", "),
+            },
+            format_count(self.filtered_events.len()),
+        );
+        if title != self.window_title {
+            frame.set_window_title(&title);
+            self.window_title = title;
+        }
         egui::TopBottomPanel::top("controls").show(ctx, |ui| {
             ui.horizontal(|ui| {
-                ui.label("Logs:");
-                for log in &self.available_logs {
-                    let mut sel = self.selected_logs.contains(log);
-                    ui.checkbox(&mut sel, log);
-                    if sel && !self.selected_logs.contains(log) {
-                        self.selected_logs.push(log.clone());
-                    } else if !sel {
-                        self.selected_logs.retain(|l| l != log);
-                    }
-                }
-                if ui.button("Refresh").clicked() { self.refresh_page(); }
-                if ui.button(if self.paused { "Resume" } else { "Pause" }).clicked() {
-                    self.paused = !self.paused;
-                }
+                if let Some(source) = &self.file_source {
+                    let path = source.path();
+                    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                    let kind = match source {
+                        FileSource::Evtx(_) => "File",
+                        FileSource::Query(_) => "Query",
+                        FileSource::Watch(_) => "Watching",
+                    };
+                    ui.label(format!("{}: {}", kind, name)).on_hover_text(path.display().to_string());
+                    if ui.button("Close").clicked() {
+                        self.file_source = None;
+                        self.reload_logs();
+                    }
+                } else {
+                    ui.label("Logs:");
+                    let mut logs_changed = false;
+                    let previous_key = self.log_view_key();
+                    let favorites = &self.settings.favorite_channels;
+                    let (mut ordered, others): (Vec<&String>, Vec<&String>) = self.available_logs.iter().partition(|log| favorites.contains(log));
+                    ordered.extend(others);
+                    let mut toggle_favorite = None;
+                    for log in ordered {
+                        let favorite = favorites.contains(log);
+                        let star = if favorite { "★" } else { "☆" };
+                        if ui.add(egui::Button::new(star).frame(false))
+                            .on_hover_text(if favorite { "Unpin from the front" } else { "Pin to the front of the list" })
+                            .clicked()
+                        {
+                            toggle_favorite = Some(log.clone());
+                        }
+                        let mut sel = self.selected_logs.contains(log);
+                        if ui.checkbox(&mut sel, log).changed() {
+                            logs_changed = true;
+                        }
+                        if sel && !self.selected_logs.contains(log) {
+                            self.selected_logs.push(log.clone());
+                        } else if !sel {
+                            self.selected_logs.retain(|l| l != log);
+                        }
+                    }
+                    let pinned: Vec<String> = self.available_logs.iter().filter(|log| favorites.contains(log)).cloned().collect();
+                    if !pinned.is_empty() && ui.button("★ only").on_hover_text("Select just the starred logs").clicked() {
+                        logs_changed = self.selected_logs != pinned;
+                        self.selected_logs = pinned;
+                    }
+                    if let Some(log) = toggle_favorite {
+                        if favorites.contains(&log) {
+                            self.settings.favorite_channels.retain(|l| *l != log);
+                        } else {
+                            self.settings.favorite_channels.push(log);
+                        }
+                    }
+                    if logs_changed {
+                        *self.live_logs.lock().unwrap() = self.selected_logs.clone();
+                        self.save_log_view(previous_key);
+                        self.reload_logs();
+                        match &mut self.pending_load {
+                            Some(pending) => pending.restore_view = true,
+                            None => self.restore_log_view(),
+                        }
+                    }
+                }
+                if ui.button("Refresh").on_hover_text("F5").clicked() { self.refresh_page(); }
+                if let Some(pending) = &self.pending_load {
+                    ui.spinner();
+                    let mut text = format!("Loading… {}s", pending.started.elapsed().as_secs());
+                    if pending.received > 0 {
+                        text.push_str(&format!(", {} events", format_count(pending.received)));
+                    }
+                    ui.label(text);
+                    if ui.button("Cancel").on_hover_text("Keep the events shown now and ignore this query's results").clicked() {
+                        self.cancel_load();
+                    }
+                }
+                ui.toggle_value(&mut self.settings.sound_muted, "🔇").on_hover_text("Mute alert sounds");
+                ui.toggle_value(&mut self.follow, "Follow").on_hover_text("Select the newest event as it arrives");
+                if ui.button(if self.paused { "Resume" } else { "Pause" }).on_hover_text("Space or Ctrl+Space").clicked() {
+                    self.paused = !self.paused;
+                }
+                if self.paused && !self.paused_buffer.is_empty() {
+                    let text = format!("{} events buffered while paused", format_count(self.paused_buffer.len()));
+                    let label = ui.label(egui::RichText::new(text).weak());
+                    if self.paused_dropped > 0 {
+                        label.on_hover_text(format!("{} older events were dropped", format_count(self.paused_dropped)));
+                    }
+                }
+                let mut problems_only = self.filters.levels.iter().map(String::as_str).eq(PROBLEM_LEVELS);
+                if ui.toggle_value(&mut problems_only, "⚠ Errors & Warnings").clicked() {
+                    self.filters.levels = if problems_only {
+                        PROBLEM_LEVELS.iter().map(|l| l.to_string()).collect()
+                    } else {
+                        Vec::new()
+                    };
+                    self.apply_filters();
+                }
+                if ui.button("Open .evtx…").on_hover_text("View an .evtx file as the event source; Refresh re-reads it").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().add_filter("Event Log", &["evtx"]).pick_file() {
+                        self.file_source = Some(FileSource::Evtx(path));
+                        self.selected = None;
+                        self.reload_logs();
+                    }
+                }
+                if ui.button("Load query file…").on_hover_text("Run a saved wevtutil structured query (QueryList XML)").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().add_filter("Structured query", &["xml"]).pick_file() {
+                        self.file_source = Some(FileSource::Query(path));
+                        self.selected = None;
+                        self.reload_logs();
+                    }
+                }
+                if ui.button("Watch log file…").on_hover_text("Tail a text log as it is written, using the text import pattern").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().add_filter("Text log", &["log", "txt"]).pick_file() {
+                        self.file_source = Some(FileSource::Watch(path));
+                        self.selected = None;
+                        self.reload_logs();
+                    }
+                }
                 if ui.button("Import File").clicked() {
-                    if let Some(path) = rfd::FileDialog::new().add_filter("Event Files", &["evtx", "xml", "csv"]).pick_file() {
-                        if let Some(path_str) = path.to_str() {
-                            self.import_file(path_str);
-                            if !self.filtered_events.is_empty() {
-                                self.selected = Some(0);
+                    self.pick_and_import();
+                }
+                if ui.button("Open folder…").on_hover_text("Import every .evtx file under a folder, including subfolders").clicked() {
+                    self.pick_and_import_folder();
+                }
+                let baseline_label = match &self.baseline {
+                    Some(baseline) => format!("Baseline: {}", baseline.name),
+                    None => "Baseline".to_string(),
+                };
+                ui.menu_button(baseline_label, |ui| {
+                    if ui.button("Load baseline .evtx…").on_hover_text("Events also in the snapshot count as seen; matched by log and RecordID").clicked() {
+                        ui.close_menu();
+                        self.pick_baseline();
+                    }
+                    if ui.button("Use loaded events as baseline").clicked() {
+                        ui.close_menu();
+                        let keys = self.all_events.iter().map(event_key).collect();
+                        self.set_baseline("snapshot".to_string(), keys);
+                    }
+                    if self.baseline.is_some() {
+                        if ui.checkbox(&mut self.filters.new_since_baseline, "New since baseline only").changed() {
+                            self.apply_filters();
+                        }
+                        if ui.button("Clear baseline").clicked() {
+                            ui.close_menu();
+                            self.baseline = None;
+                            self.apply_filters();
+                        }
+                    }
+                });
+                ui.menu_button("Export", |ui| {
+                    ui.checkbox(&mut self.anonymize_exports, "Anonymize")
+                        .on_hover_text("Replace usernames, SIDs, IP addresses and computer names with pseudonyms");
+                    ui.checkbox(&mut self.export_data_fields, "Include data fields")
+                        .on_hover_text("Add each named EventData/UserData field as a CSV column, or a nested data object in JSON");
+                    ui.separator();
+                    if ui.button("Copy filtered as XML").clicked() {
+                        let events = self.export_events();
+                        ui.ctx().output_mut(|o| o.copied_text = export::events_to_xml(&events));
+                        self.status = format!("Copied {} events as XML", format_count(self.filtered_events.len()));
+                        ui.close_menu();
+                    }
+                    if ui.button("Save filtered as XML…").clicked() {
+                        ui.close_menu();
+                        if let Some(path) = rfd::FileDialog::new().add_filter("XML", &["xml"]).set_file_name("events.xml").save_file() {
+                            let events = self.export_events();
+                            self.status = match std::fs::write(&path, export::events_to_xml(&events)) {
+                                Ok(()) => format!("Saved {} events to {}", format_count(self.filtered_events.len()), path.display()),
+                                Err(e) => format!("Export failed: {}", e),
+                            };
+                        }
+                    }
+                    if ui.button("Save each filtered event as XML…").on_hover_text("One file per event, named by log, RecordID and time").clicked() {
+                        ui.close_menu();
+                        if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                            let events = self.export_events();
+                            self.status = match export::events_to_files(&events, &dir) {
+                                Ok(n) => format!("Saved {} event files to {}", format_count(n), dir.display()),
+                                Err(e) => format!("Export failed: {}", e),
+                            };
+                        }
+                    }
+                    if ui.button("Copy filtered as Markdown").clicked() {
+                        let events = self.export_events();
+                        ui.ctx().output_mut(|o| o.copied_text = export::events_to_markdown(&events, self.settings.markdown_message_width, &self.time_format));
+                        self.status = format!("Copied {} events as Markdown", format_count(events.len()));
+                        ui.close_menu();
+                    }
+                    if ui.button("Save filtered as Markdown…").clicked() {
+                        ui.close_menu();
+                        if let Some(path) = rfd::FileDialog::new().add_filter("Markdown", &["md"]).set_file_name("events.md").save_file() {
+                            let events = self.export_events();
+                            self.status = match std::fs::write(&path, export::events_to_markdown(&events, self.settings.markdown_message_width, &self.time_format)) {
+                                Ok(()) => format!("Saved {} events to {}", format_count(events.len()), path.display()),
+                                Err(e) => format!("Export failed: {}", e),
+                            };
+                        }
+                    }
+                    if ui.button("Save filtered as JSON…").clicked() {
+                        ui.close_menu();
+                        if let Some(path) = rfd::FileDialog::new().add_filter("JSON", &["json"]).set_file_name("events.json").save_file() {
+                            let events = self.export_events();
+                            self.status = match std::fs::write(&path, export::events_to_json(&events, self.export_data_fields)) {
+                                Ok(()) => format!("Saved {} events to {}", format_count(events.len()), path.display()),
+                                Err(e) => format!("Export failed: {}", e),
+                            };
+                        }
+                    }
+                    if ui.button("Save filtered as JSON Lines…").on_hover_text("One compact JSON object per line, for log shippers").clicked() {
+                        ui.close_menu();
+                        if let Some(path) = rfd::FileDialog::new().add_filter("JSON Lines", &["jsonl", "ndjson"]).set_file_name("events.jsonl").save_file() {
+                            let events = self.export_events();
+                            self.status = match export::events_to_jsonl(&events, self.export_data_fields, &path) {
+                                Ok(()) => format!("Saved {} events to {}", format_count(events.len()), path.display()),
+                                Err(e) => format!("Export failed: {}", e),
+                            };
+                        }
+                    }
+                    if ui.add(egui::Button::new("Save filtered as CSV…").shortcut_text("Ctrl+E")).clicked() {
+                        ui.close_menu();
+                        self.save_csv();
+                    }
+                    if ui.button("Save timeline as CSV…").clicked() {
+                        ui.close_menu();
+                        if let Some(path) = rfd::FileDialog::new().add_filter("CSV", &["csv"]).set_file_name("timeline.csv").save_file() {
+                            let bucket_secs = i64::from(self.settings.timeline_bucket_minutes) * 60;
+                            let buckets = timeline::timeline_buckets(&self.filtered_events, bucket_secs);
+                            self.status = match export::timeline_to_csv(&buckets, &path) {
+                                Ok(()) => format!("Saved {} timeline buckets to {}", format_count(buckets.len()), path.display()),
+                                Err(e) => format!("Export failed: {}", e),
+                            };
+                        }
+                    }
+                    ui.separator();
+                    if ui.button("Copy as PowerShell Get-WinEvent").on_hover_text("Command reproducing the current logs and filters").clicked() {
+                        ui.ctx().output_mut(|o| o.copied_text = self.powershell_command());
+                        self.status = "Copied Get-WinEvent command".to_string();
+                        ui.close_menu();
+                    }
+                    if self.anonymize_exports && ui.button("Save pseudonym mapping…").clicked() {
+                        ui.close_menu();
+                        if let Some(path) = rfd::FileDialog::new().add_filter("CSV", &["csv"]).set_file_name("pseudonyms.csv").save_file() {
+                            let mapping = self.anonymizer.mapping();
+                            let rows = mapping.iter().map(|(original, pseudonym)| vec![original.clone(), pseudonym.clone()]);
+                            self.status = match export::rows_to_csv(&["original", "pseudonym"], rows, &path) {
+                                Ok(()) => format!("Saved {} pseudonyms to {}", format_count(mapping.len()), path.display()),
+                                Err(e) => format!("Export failed: {}", e),
+                            };
+                        }
+                    }
+                });
+                ui.toggle_value(&mut self.xml_search_open, "Find in XML");
+                ui.toggle_value(&mut self.settings.show_details, "Details").on_hover_text("Show or hide the details panel (F9)");
+                ui.label("Jump to:");
+                let jump_edit = ui.add(egui::TextEdit::singleline(&mut self.jump_to).hint_text("YYYY-MM-DD HH:MM").desired_width(120.0));
+                let submitted = jump_edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.button("Go").clicked() || submitted {
+                    if let Some(target) = parse_jump_time(&self.jump_to) {
+                        self.jump_to_time(target);
+                    }
+                }
+                egui::ComboBox::from_id_source("data_columns").selected_text("Add column from EventData").show_ui(ui, |ui| {
+                    let mut names: Vec<&String> = self.all_events.iter().flat_map(|e| e.data.iter().map(|(k, _)| k)).collect();
+                    names.sort();
+                    names.dedup();
+                    if names.is_empty() {
+                        ui.label("No named EventData in loaded events");
+                    }
+                    for name in names {
+                        let mut shown = self.data_columns.contains(name);
+                        if ui.checkbox(&mut shown, name).changed() {
+                            if shown {
+                                self.data_columns.push(name.clone());
+                            } else {
+                                self.data_columns.retain(|c| c != name);
                             }
                         }
                     }
+                });
+                if ui.button("Reset columns").on_hover_text("Default columns at their initial widths").clicked() {
+                    self.reset_columns();
                 }
+                ui.toggle_value(&mut self.settings_open, "Settings");
                 ui.separator();
                 ui.label("Theme:");
                 egui::ComboBox::from_id_source("theme_mode").selected_text(match self.theme_mode {
@@ -458,72 +2330,1231 @@ impl App for EventViewerApp {
             });
         });
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            egui::ScrollArea::both().show(ui, |ui| {
-                TableBuilder::new(ui)
-                    .column(Column::auto().resizable(true)) // Time
-                    .column(Column::initial(60.0)) // Level
-                    .column(Column::initial(60.0)) // EventID
-                    .column(Column::initial(100.0)) // Source
-                    .column(Column::initial(120.0)) // Username
-                    .column(Column::initial(180.0)) // Computer
-                    .striped(true)
-                    .resizable(true)
-                    .header(20.0, |mut header| {
-                        header.col(|ui| { ui.label("Time"); });
-                        header.col(|ui| { ui.label("Level"); });
-                        header.col(|ui| { ui.label("ID"); });
-                        header.col(|ui| { ui.label("Source"); });
-                        header.col(|ui| { ui.label("Username"); });
-                        header.col(|ui| { ui.label("Computer"); });
+        egui::TopBottomPanel::top("filters").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let mut changed = false;
+                let levels_label = if self.filters.levels.is_empty() {
+                    "Levels: all".to_string()
+                } else {
+                    format!("Levels: {}", self.filters.levels.len())
+                };
+                ui.menu_button(levels_label, |ui| {
+                    for level in LEVELS {
+                        let mut on = self.filters.levels.iter().any(|l| l == level);
+                        if ui.checkbox(&mut on, egui::RichText::new(level).color(level_color(level, ui.visuals()))).changed() {
+                            if on {
+                                self.filters.levels.push(level.to_string());
+                            } else {
+                                self.filters.levels.retain(|l| l != level);
+                            }
+                            changed = true;
+                        }
+                    }
+                });
+                egui::ComboBox::from_id_source("audit_filter")
+                    .selected_text(match self.filters.audit {
+                        AuditFilter::All => "Audit: any".to_string(),
+                        audit => audit.label().to_string(),
+                    })
+                    .show_ui(ui, |ui| {
+                        for audit in [AuditFilter::All, AuditFilter::Success, AuditFilter::Failure, AuditFilter::Both] {
+                            changed |= ui.selectable_value(&mut self.filters.audit, audit, audit.label()).changed();
+                        }
                     })
-                    .body(|body| {
-                        body.rows(20.0, self.filtered_events.len(), |row_index, mut row| {
-                            let ev = &self.filtered_events[row_index];
-                            let selected = self.selected == Some(row_index);
-                            let response = row.col(|ui| {
-                                let label = ui.selectable_label(selected, ev.time_created.format("%Y-%m-%d %H:%M:%S").to_string());
-                                if label.clicked() {
-                                    self.selected = Some(row_index);
+                    .response
+                    .on_hover_text("Security audit outcome from the Keywords mask");
+                ui.label("Source:");
+                let source_edit = ui.add(egui::TextEdit::singleline(&mut self.filters.source).desired_width(100.0));
+                changed |= source_edit.changed();
+                // Suggest the providers present in the loaded events while typing
+                let popup_id = ui.make_persistent_id("source_suggestions");
+                let typed = self.filters.source.to_lowercase();
+                // Still listed while the popup is open, since clicking an entry takes focus from the field
+                let listing = source_edit.has_focus() || ui.memory(|m| m.is_popup_open(popup_id));
+                let suggestions: Vec<String> = if listing && !typed.is_empty() {
+                    let names: BTreeSet<&String> = self.all_events.iter()
+                        .map(|e| &e.source)
+                        .filter(|s| s.to_lowercase().contains(&typed) && **s != self.filters.source)
+                        .collect();
+                    names.into_iter().take(10).cloned().collect()
+                } else {
+                    Vec::new()
+                };
+                if !suggestions.is_empty() {
+                    ui.memory_mut(|m| m.open_popup(popup_id));
+                } else if source_edit.has_focus() {
+                    ui.memory_mut(|m| if m.is_popup_open(popup_id) { m.close_popup() });
+                }
+                egui::popup_below_widget(ui, popup_id, &source_edit, |ui| {
+                    ui.set_min_width(220.0);
+                    for name in &suggestions {
+                        if ui.selectable_label(false, name).clicked() {
+                            self.filters.source = name.clone();
+                            changed = true;
+                            ui.memory_mut(|m| m.close_popup());
+                        }
+                    }
+                });
+                ui.label("Event ID:");
+                let ids_valid = self.event_id_input.trim().is_empty() || parse_event_ids(&self.event_id_input).is_some();
+                let mut id_edit = egui::TextEdit::singleline(&mut self.event_id_input).desired_width(90.0);
+                if !ids_valid {
+                    id_edit = id_edit.text_color(ui.visuals().error_fg_color);
+                }
+                if ui.add(id_edit).on_hover_text("One ID, a list and/or ranges, e.g. 4624,4625,4634 or 4720-4738").changed() {
+                    self.filters.event_ids = parse_event_ids(&self.event_id_input).unwrap_or_default();
+                    changed = true;
+                }
+                ui.label("Task:");
+                if ui.add(egui::TextEdit::singleline(&mut self.task_input).desired_width(50.0)).changed() {
+                    self.filters.task = self.task_input.trim().parse().ok();
+                    changed = true;
+                }
+                ui.label("User:");
+                changed |= ui.add(egui::TextEdit::singleline(&mut self.filters.user).desired_width(100.0)).changed();
+                ui.label("Hours:");
+                for (input, time, hint) in [
+                    (&mut self.time_from_input, &mut self.filters.time_from, "from"),
+                    (&mut self.time_to_input, &mut self.filters.time_to, "to"),
+                ] {
+                    let valid = input.trim().is_empty() || parse_time_of_day(input).is_some();
+                    let mut edit = egui::TextEdit::singleline(input).hint_text(hint).desired_width(50.0);
+                    if !valid {
+                        edit = edit.text_color(ui.visuals().error_fg_color);
+                    }
+                    if ui.add(edit).on_hover_text("Time of day on every date, e.g. 00:00 to 06:00; 22:00 to 06:00 spans midnight").changed() {
+                        *time = parse_time_of_day(input);
+                        changed = true;
+                    }
+                }
+                let computers_label = match self.filters.computers.len() {
+                    0 => "Computers: all".to_string(),
+                    1 => format!("Computer: {}", self.filters.computers.iter().next().unwrap()),
+                    n => format!("Computers: {}", n),
+                };
+                ui.menu_button(computers_label, |ui| {
+                    if ui.button("Show all").clicked() {
+                        self.filters.computers.clear();
+                        changed = true;
+                    }
+                    ui.separator();
+                    let known: BTreeSet<&String> = self.all_events.iter().map(|e| &e.computer).collect();
+                    egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                        for name in known {
+                            let mut on = self.filters.computers.contains(name);
+                            if ui.checkbox(&mut on, name.as_str()).changed() {
+                                if on {
+                                    self.filters.computers.insert(name.clone());
+                                } else {
+                                    self.filters.computers.remove(name);
                                 }
-                            });
-                            row.col(|ui| { ui.label(&ev.level); });
-                            row.col(|ui| { ui.label(ev.event_id.to_string()); });
-                            row.col(|ui| { ui.label(&ev.source); });
-                            row.col(|ui| { ui.label(&ev.user); }); // Now Username
-                            row.col(|ui| { ui.label(&ev.computer); });
-                        });
+                                changed = true;
+                            }
+                        }
                     });
+                });
+                ui.label("Keyword:");
+                changed |= ui.add(egui::TextEdit::singleline(&mut self.filters.keyword).id(keyword_id).hint_text("terms or \"a phrase\"").desired_width(160.0))
+                    .on_hover_text("Ctrl+F")
+                    .changed();
+                changed |= ui.selectable_value(&mut self.filters.keyword_mode, KeywordMode::All, "AND").changed();
+                changed |= ui.selectable_value(&mut self.filters.keyword_mode, KeywordMode::Any, "OR").changed();
+                changed |= ui.toggle_value(&mut self.filters.match_case, "Aa").on_hover_text("Match case in text filters").changed();
+                changed |= ui.toggle_value(&mut self.filters.whole_word, "W").on_hover_text("Match keyword terms as whole words only").changed();
+                ui.menu_button("Search in…", |ui| {
+                    let scope = &mut self.filters.keyword_scope;
+                    changed |= ui.checkbox(&mut scope.description, "Description").changed();
+                    changed |= ui.checkbox(&mut scope.raw_xml, "Raw XML").changed();
+                    changed |= ui.checkbox(&mut scope.source, "Source").changed();
+                    changed |= ui.checkbox(&mut scope.user, "User").changed();
+                });
+                ui.separator();
+                changed |= ui.checkbox(&mut self.collapse_duplicates, "Collapse duplicates").changed();
+                changed |= ui.checkbox(&mut self.group_by_day, "Group by day").on_hover_text("One expandable heading per date, in the current sort order").changed();
+                changed |= ui.checkbox(&mut self.filters.hide_empty_descriptions, "Hide empty descriptions").changed();
+                changed |= ui.checkbox(&mut self.show_column_filters, "Column filters").changed();
+                ui.separator();
+                let limit = self.view_limit;
+                ui.selectable_value(&mut self.view_limit, ViewLimit::All, "All");
+                ui.selectable_value(&mut self.view_limit, ViewLimit::First(QUICK_LIMIT), format!("First {}", QUICK_LIMIT));
+                ui.selectable_value(&mut self.view_limit, ViewLimit::Last(QUICK_LIMIT), format!("Last {}", QUICK_LIMIT));
+                if changed {
+                    self.apply_filters();
+                } else if self.view_limit != limit {
+                    self.rebuild_view_rows();
+                }
+            });
+        });
+
+        egui::TopBottomPanel::bottom("status").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let (shown, total) = (self.filtered_events.len(), self.all_events.len());
+                if shown == total {
+                    ui.label(format!("{} events", format_count(total)));
+                } else {
+                    ui.label(format!("Showing {} of {}", format_count(shown), format_count(total)))
+                        .on_hover_text(format!("{} loaded events are hidden by the current filters", format_count(total - shown)));
+                }
+                match self.view_limit {
+                    ViewLimit::All => {}
+                    ViewLimit::First(n) => { ui.label(format!("(showing first {})", n)); }
+                    ViewLimit::Last(n) => { ui.label(format!("(showing last {})", n)); }
+                }
+                // Per-level counts; clicking one shows only that level
+                for level in LEVELS {
+                    let count = self.filtered_events.iter().filter(|e| e.level == level).count();
+                    if count == 0 {
+                        continue;
+                    }
+                    let text = egui::RichText::new(format!("{} {}", format_count(count), level))
+                        .color(level_color(level, ui.visuals()));
+                    if ui.selectable_label(false, text).clicked() {
+                        self.filters.levels = vec![level.to_string()];
+                        self.apply_filters();
+                    }
+                }
+                if self.file_source.is_none() {
+                    ui.separator();
+                    let minutes = self.settings.error_badge_minutes;
+                    let since = Local::now() - chrono::Duration::minutes(i64::from(minutes));
+                    let errors = self.all_events.iter()
+                        .filter(|e| level_severity(&e.level) >= level_severity("Error") && e.time_created >= since)
+                        .count();
+                    let window = if minutes == 60 { "the last hour".to_string() } else { format!("the last {} min", minutes) };
+                    let text = egui::RichText::new(format!("{} errors in {}", format_count(errors), window));
+                    ui.label(if errors > 0 { text.color(ui.visuals().error_fg_color) } else { text.weak() })
+                        .on_hover_text("Error and Critical events among those loaded");
+                    ui.separator();
+                    ui.label(format!("{}/min", self.live_arrivals.len()))
+                        .on_hover_text("New live events in the last minute");
+                    let mut buckets = [0usize; RATE_BUCKETS];
+                    let bucket_secs = RATE_WINDOW.as_secs_f32() / RATE_BUCKETS as f32;
+                    for at in &self.live_arrivals {
+                        let age = (at.elapsed().as_secs_f32() / bucket_secs) as usize;
+                        buckets[RATE_BUCKETS - 1 - age.min(RATE_BUCKETS - 1)] += 1;
+                    }
+                    let (rect, _) = ui.allocate_exact_size(egui::vec2(RATE_BUCKETS as f32 * 4.0, 14.0), egui::Sense::hover());
+                    let max = buckets.iter().copied().max().unwrap_or(0).max(1) as f32;
+                    let color = ui.visuals().text_color();
+                    for (i, count) in buckets.iter().enumerate() {
+                        let height = rect.height() * *count as f32 / max;
+                        let x = rect.left() + i as f32 * 4.0;
+                        let bar = egui::Rect::from_min_max(egui::pos2(x, rect.bottom() - height), egui::pos2(x + 3.0, rect.bottom()));
+                        ui.painter().rect_filled(bar, 0.0, color);
+                    }
+                }
+                if self.type_ahead_active() {
+                    ui.separator();
+                    let needle = self.type_ahead.to_lowercase();
+                    let matched = self.selected.and_then(|idx| self.filtered_events.get(idx)).is_some_and(|ev| type_ahead_match(ev, &needle));
+                    let text = format!("Find: {}", self.type_ahead);
+                    if matched {
+                        ui.label(text);
+                    } else {
+                        ui.colored_label(ui.visuals().error_fg_color, format!("{} (no match)", text));
+                    }
+                }
+                if let Some(at) = self.last_poll {
+                    ui.separator();
+                    ui.label(format!("Last checked {}", at.format("%H:%M:%S")));
+                }
+                if let Some(e) = &self.poll_error {
+                    ui.separator();
+                    ui.colored_label(ui.visuals().error_fg_color, format!("Live polling failing: {}", e));
+                }
+                if !self.status.is_empty() {
+                    ui.separator();
+                    ui.label(&self.status);
+                }
+            });
+        });
+
+        let mut settings_open = self.settings_open;
+        egui::Window::new("Settings").open(&mut settings_open).show(ctx, |ui| {
+            ui.label("Event ID lookup URL ({source} and {id} are replaced):");
+            ui.text_edit_singleline(&mut self.settings.lookup_url_template);
+            ui.checkbox(&mut self.settings.show_task_column, "Show Task Category column");
+            ui.checkbox(&mut self.settings.show_delta_column, "Show Δt column (time since the previous row)");
+            ui.checkbox(&mut self.settings.show_minimap, "Show level overview beside the table");
+            ui.horizontal(|ui| {
+                ui.label("Timeline bucket (minutes):");
+                ui.add(egui::DragValue::new(&mut self.settings.timeline_bucket_minutes).clamp_range(1..=1440));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Longest Source / User / Computer cell (characters):");
+                ui.add(egui::DragValue::new(&mut self.settings.max_cell_chars).clamp_range(8..=500));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Error count window (minutes):");
+                ui.add(egui::DragValue::new(&mut self.settings.error_badge_minutes).clamp_range(1..=1440));
+            });
+            if ui.checkbox(&mut self.settings.rendered_xml, "Query rendered XML")
+                .on_hover_text("Ask wevtutil for /f:RenderedXml, which adds the formatted message and task/keyword names; falls back to plain XML when rendering fails")
+                .changed()
+            {
+                set_rendered_xml(self.settings.rendered_xml);
+                self.refresh_page();
+            }
+            ui.horizontal(|ui| {
+                ui.label("Query timeout (seconds):");
+                if ui.add(egui::DragValue::new(&mut self.settings.query_timeout_secs).clamp_range(0..=3600))
+                    .on_hover_text("wevtutil is stopped and the query reported as timed out after this long; 0 waits indefinitely")
+                    .changed()
+                {
+                    set_query_timeout(self.settings.query_timeout_secs);
+                }
+            });
+            ui.checkbox(&mut self.settings.keep_raw_xml, "Keep raw XML")
+                .on_hover_text("Turn off to roughly halve memory use; applies to newly loaded events");
+            if ui.checkbox(&mut self.settings.cache_imports, "Remember the last import for the next session")
+                .on_hover_text("Saves a copy of imported events in the app's data folder")
+                .changed()
+                && !self.settings.cache_imports
+            {
+                self.forget_import_cache();
+            }
+            ui.checkbox(&mut self.settings.folder_host_from_parent, "Open folder: use each file's folder name as the computer")
+                .on_hover_text("For collections laid out as one folder of .evtx files per host");
+            ui.horizontal(|ui| {
+                ui.label("Keep live events at or above:");
+                let before = self.settings.live_min_severity;
+                egui::ComboBox::from_id_source("live_min_severity")
+                    .selected_text(severity_label(self.settings.live_min_severity))
+                    .show_ui(ui, |ui| {
+                        for severity in [0, 1, 2, 3, 4, 5] {
+                            ui.selectable_value(&mut self.settings.live_min_severity, severity, severity_label(severity));
+                        }
+                    });
+                if self.settings.live_min_severity != before {
+                    self.live_min_severity.store(self.settings.live_min_severity, Ordering::Relaxed);
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Sound on live:").on_hover_text("Alert sounds play on Windows only");
+                ui.checkbox(&mut self.settings.sound_on_critical, "Critical");
+                ui.checkbox(&mut self.settings.sound_on_error, "Error");
+                ui.checkbox(&mut self.settings.sound_muted, "Mute all");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Markdown message width:");
+                ui.add(egui::DragValue::new(&mut self.settings.markdown_message_width).clamp_range(10..=2000));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Raw XML display limit (KB):");
+                ui.add(egui::DragValue::new(&mut self.settings.max_xml_display_kb).clamp_range(1..=16384));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Description from Data:");
+                let before = (self.settings.description_separator.clone(), self.settings.description_names);
+                egui::ComboBox::from_id_source("description_separator")
+                    .selected_text(separator_label(&self.settings.description_separator))
+                    .show_ui(ui, |ui| {
+                        for separator in DESCRIPTION_SEPARATORS {
+                            ui.selectable_value(&mut self.settings.description_separator, separator.to_string(), separator_label(separator));
+                        }
+                    });
+                ui.checkbox(&mut self.settings.description_names, "Name=Value");
+                if (self.settings.description_separator.clone(), self.settings.description_names) != before {
+                    set_description_format(&self.settings.description_separator, self.settings.description_names);
+                    self.refresh_page();
+                }
             });
+            ui.horizontal(|ui| {
+                ui.label("Time format:");
+                let valid = valid_time_format(&self.settings.time_format);
+                let mut edit = egui::TextEdit::singleline(&mut self.settings.time_format).desired_width(180.0);
+                if !valid {
+                    edit = edit.text_color(ui.visuals().error_fg_color);
+                }
+                let mut changed = ui.add(edit).on_hover_text("chrono strftime syntax, e.g. %Y-%m-%d %I:%M:%S%.3f %p").changed();
+                egui::ComboBox::from_id_source("time_format_preset")
+                    .selected_text("Presets")
+                    .show_ui(ui, |ui| {
+                        for (name, format) in TIME_FORMAT_PRESETS {
+                            changed |= ui.selectable_value(&mut self.settings.time_format, format.to_string(), name).changed();
+                        }
+                    });
+                if changed {
+                    self.time_format = checked_time_format(&self.settings.time_format);
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Row density:");
+                ui.selectable_value(&mut self.settings.density, Density::Compact, "Compact");
+                ui.selectable_value(&mut self.settings.density, Density::Normal, "Normal");
+                ui.selectable_value(&mut self.settings.density, Density::Comfortable, "Comfortable");
+            });
+            ui.separator();
+            ui.label("Text log line pattern (named groups: time, level, source, message):");
+            ui.text_edit_singleline(&mut self.settings.text_import_pattern);
+            ui.horizontal(|ui| {
+                ui.label("Presets:");
+                for (name, pattern) in TEXT_IMPORT_PRESETS {
+                    if ui.button(name).clicked() {
+                        self.settings.text_import_pattern = pattern.to_string();
+                    }
+                }
+            });
+            if let Err(e) = regex::Regex::new(&self.settings.text_import_pattern) {
+                ui.colored_label(egui::Color32::RED, e.to_string());
+            }
+            ui.separator();
+            if ui.button("Reset all settings to defaults…").clicked() {
+                self.confirm_reset = true;
+            }
+        });
+        self.settings_open = settings_open;
+        if self.confirm_reset {
+            egui::Window::new("Reset settings?")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.label("Restore every setting, the theme, filters, columns and panel sizes to their defaults?");
+                    ui.horizontal(|ui| {
+                        if ui.button("Reset").clicked() {
+                            self.reset_settings(ctx);
+                            self.confirm_reset = false;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.confirm_reset = false;
+                        }
+                    });
+                });
+        }
+
+        let mut xml_search_open = self.xml_search_open;
+        egui::Window::new("Find in Raw XML").open(&mut xml_search_open).show(ctx, |ui| {
+            if ui.text_edit_singleline(&mut self.xml_search).changed() {
+                self.run_xml_search();
+            }
+            if self.xml_search_hits.len() >= XML_SEARCH_MAX_HITS {
+                ui.label(format!("Showing first {} matches", XML_SEARCH_MAX_HITS));
+            } else if !self.xml_search.is_empty() {
+                ui.label(format!("{} matching events", self.xml_search_hits.len()));
+            }
+            ui.separator();
+            let mut clicked = None;
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for (ev, snippet) in &self.xml_search_hits {
+                    let title = format!("{}  {}  {}", format_time(&ev.time_created, &self.time_format), ev.event_id, ev.source);
+                    if ui.selectable_label(false, title).clicked() {
+                        clicked = self.filtered_events.iter().position(|e| e.same_event(ev));
+                    }
+                    ui.monospace(snippet);
+                    ui.separator();
+                }
+            });
+            if let Some(idx) = clicked {
+                self.selected = Some(idx);
+                self.scroll_to_row = self.view_row_of(idx).map(|row| (row, egui::Align::Center));
+            }
         });
-        egui::SidePanel::right("details").resizable(true).show(ctx, |ui| {
+        self.xml_search_open = xml_search_open;
+
+        let selected_event = self.selected.and_then(|i| self.filtered_events.get(i));
+        if self.full_xml_for.as_ref().is_some_and(|full| !selected_event.is_some_and(|ev| ev.same_event(full))) {
+            self.full_xml_for = None;
+        }
+        let panel = egui::SidePanel::right("details").resizable(true).default_width(self.settings.details_width);
+        let details = panel.show_animated(ctx, self.settings.show_details, |ui| {
             egui::ScrollArea::both().show(ui, |ui| {
-                ui.heading("Event Details");
+                let mut xml_action = None;
+                let mut save_binary: Option<Vec<u8>> = None;
+                ui.horizontal(|ui| {
+                    ui.heading("Event Details");
+                    let current = self.selected.unwrap_or(0);
+                    let has_prev = current > 0;
+                    let has_next = current + 1 < self.filtered_events.len();
+                    if ui.add_enabled(has_prev, egui::Button::new("<")).on_hover_text("Previous event (Alt+Left)").clicked() {
+                        self.select_relative(-1);
+                    }
+                    if ui.add_enabled(has_next, egui::Button::new(">")).on_hover_text("Next event (Alt+Right)").clicked() {
+                        self.select_relative(1);
+                    }
+                });
                 if let Some(ev) = self.filtered_events.get(self.selected.unwrap_or(0)) {
-                    ui.label(format!("Log: {}", ev.log_name));
-                    ui.separator();
-                    ui.label(format!("Time: {}", ev.time_created));
-                    ui.label(format!("Level: {}", ev.level));
-                    ui.label(format!("Event ID: {}", ev.event_id));
-                    ui.label(format!("Source: {}", ev.source));
-                    ui.label(format!("Username: {}", ev.user));
-                    ui.label(format!("Computer: {}", ev.computer));
+                    egui::CollapsingHeader::new("System").default_open(true).show(ui, |ui| {
+                        egui::Grid::new("details_system").num_columns(2).striped(true).show(ui, |ui| {
+                            ui.label("Provider");
+                            ui.label(&ev.source);
+                            ui.end_row();
+                            if let Some(guid) = &ev.provider_guid {
+                                ui.label("Provider GUID");
+                                ui.horizontal(|ui| {
+                                    ui.monospace(guid);
+                                    if ui.small_button("Copy").clicked() {
+                                        ui.ctx().output_mut(|o| o.copied_text = guid.clone());
+                                    }
+                                });
+                                ui.end_row();
+                            }
+                            ui.label("EventID");
+                            ui.horizontal(|ui| {
+                                ui.label(ev.event_id.to_string());
+                                let url = self.settings.lookup_url_template
+                                    .replace("{source}", &url_encode(&ev.source))
+                                    .replace("{id}", &ev.event_id.to_string());
+                                ui.hyperlink_to("Lookup online", url);
+                            });
+                            ui.end_row();
+                            ui.label("Version");
+                            ui.label(ev.version.to_string());
+                            ui.end_row();
+                            ui.label("Level");
+                            ui.label(format!("{} ({})", ev.level, ev.level_num));
+                            ui.end_row();
+                            ui.label("Task");
+                            ui.label(format!("{} ({})", ev.task_label(), ev.task));
+                            ui.end_row();
+                            ui.label("Opcode");
+                            ui.label(format!("{} ({})", ev.opcode_label(), ev.opcode));
+                            ui.end_row();
+                            ui.label("Keywords");
+                            if ev.keyword_names.is_empty() {
+                                ui.label(&ev.keywords);
+                            } else {
+                                ui.label(format!("{} ({})", ev.keywords, ev.keyword_names.join(", ")));
+                            }
+                            ui.end_row();
+                            if let Some(result) = ev.audit_result() {
+                                ui.label("Audit");
+                                ui.colored_label(level_color(result, ui.visuals()), result);
+                                ui.end_row();
+                            }
+                            ui.label("TimeCreated");
+                            ui.label(format_time(&ev.time_created, &self.time_format))
+                                .on_hover_text(ev.time_created.to_rfc3339());
+                            ui.end_row();
+                            ui.label("EventRecordID");
+                            ui.horizontal(|ui| {
+                                ui.label(if ev.record_id != 0 { ev.record_id.to_string() } else { String::new() });
+                                if self.fetching_detail.as_ref().is_some_and(|f| f.same_event(ev)) {
+                                    ui.spinner();
+                                } else if self.file_source.is_none() && ev.record_id != 0 && !ev.log_name.is_empty()
+                                    && ui.add_enabled(self.fetching_detail.is_none(), egui::Button::new("Fetch full detail").small())
+                                        .on_hover_text("Query just this event again with its rendered message")
+                                        .clicked()
+                                {
+                                    self.fetching_detail = Some(ev.clone());
+                                    let old = Box::new(ev.clone());
+                                    in_background(&self.load_tx, move || {
+                                        let result = query_record(&old.log_name, old.record_id).map(Box::new);
+                                        LoadMessage::Record(old, result)
+                                    });
+                                }
+                            });
+                            ui.end_row();
+                            ui.label("Correlation");
+                            ui.label(&ev.activity_id);
+                            ui.end_row();
+                            ui.label("Execution");
+                            ui.label(format!("ProcessID {}, ThreadID {}", ev.process_id, ev.thread_id));
+                            ui.end_row();
+                            ui.label("Channel");
+                            ui.vertical(|ui| {
+                                ui.horizontal(|ui| {
+                                    ui.label(&ev.log_name);
+                                    if !ev.log_name.is_empty() {
+                                        if ui.small_button("Copy").clicked() {
+                                            ui.ctx().output_mut(|o| o.copied_text = ev.log_name.clone());
+                                        }
+                                        if cfg!(target_os = "windows") && self.file_source.is_none()
+                                            && ui.small_button("Open in Event Viewer").clicked()
+                                        {
+                                            if let Err(e) = open_in_event_viewer(&ev.log_name) {
+                                                self.status = e;
+                                            }
+                                        }
+                                    }
+                                });
+                                // Only live events come from this machine's channel of that name
+                                if self.file_source.is_none() && !ev.log_name.is_empty() {
+                                    let info = self.channel_info.entry(ev.log_name.clone()).or_insert_with(|| {
+                                        let channel = ev.log_name.clone();
+                                        in_background(&self.load_tx, move || {
+                                            let info = channel_info(&channel);
+                                            LoadMessage::ChannelInfo(channel, info)
+                                        });
+                                        None
+                                    });
+                                    match info {
+                                        Some(Ok(info)) => channel_summary(ui, info),
+                                        Some(Err(e)) => { ui.weak(e.as_str()); }
+                                        None => {
+                                            ui.horizontal(|ui| {
+                                                ui.spinner();
+                                                ui.weak("Reading channel details…");
+                                            });
+                                        }
+                                    }
+                                }
+                            });
+                            ui.end_row();
+                            ui.label("Computer");
+                            ui.label(&ev.computer);
+                            ui.end_row();
+                            ui.label("Security");
+                            ui.horizontal(|ui| {
+                                ui.label(&ev.user);
+                                if ev.user.starts_with("S-1-") {
+                                    if ui.small_button("Copy SID").clicked() {
+                                        ui.ctx().output_mut(|o| o.copied_text = ev.user.clone());
+                                    }
+                                    if let Some(name) = well_known_sid(&ev.user) {
+                                        ui.label(format!("→ {}", name));
+                                    } else if let Some(resolved) = self.sid_names.get(&ev.user) {
+                                        match resolved {
+                                            Some(name) => ui.label(format!("→ {}", name)),
+                                            None => ui.spinner(),
+                                        };
+                                    } else if ui.small_button("Resolve").clicked() {
+                                        self.sid_names.insert(ev.user.clone(), None);
+                                        let sid = ev.user.clone();
+                                        in_background(&self.load_tx, move || {
+                                            let name = resolve_sid(&sid);
+                                            LoadMessage::Sid(sid, name)
+                                        });
+                                    }
+                                }
+                            });
+                            ui.end_row();
+                        });
+                    });
+                    egui::CollapsingHeader::new("EventData").default_open(true).show(ui, |ui| {
+                        if let Some(logon_type) = ev.logon_type() {
+                            ui.label(egui::RichText::new(format!("Logon Type: {}", logon_type)).strong());
+                        }
+                        if ev.data.is_empty() {
+                            ui.label("No named data");
+                        }
+                        egui::Grid::new("details_event_data").num_columns(2).striped(true).show(ui, |ui| {
+                            for (name, value) in &ev.data {
+                                ui.label(name);
+                                ui.label(value);
+                                ui.end_row();
+                            }
+                        });
+                    });
+                    // Without RenderingInfo the description is only the joined Data values
+                    let rendered = ev.rendering.as_ref().is_some_and(|r| !r.message.trim().is_empty());
+                    if !rendered && !ev.data.is_empty() && !ev.source.is_empty() {
+                        egui::CollapsingHeader::new("Explain this event").show(ui, |ui| {
+                            let templates = self.templates.entry(ev.source.clone()).or_insert_with(|| {
+                                let provider = ev.source.clone();
+                                in_background(&self.load_tx, move || {
+                                    let templates = provider_templates(&provider);
+                                    LoadMessage::Templates(provider, templates)
+                                });
+                                None
+                            });
+                            let Some(templates) = templates else {
+                                ui.horizontal(|ui| {
+                                    ui.spinner();
+                                    ui.weak(format!("Reading messages of {}…", ev.source));
+                                });
+                                return;
+                            };
+                            let template = templates.as_ref().map(|t| {
+                                t.get(&(ev.event_id, ev.version))
+                                    .or_else(|| t.iter().find(|((id, _), _)| *id == ev.event_id).map(|(_, m)| m))
+                            });
+                            match template {
+                                Ok(Some(template)) => {
+                                    let values: Vec<&str> = ev.data.iter().map(|(_, v)| v.as_str()).collect();
+                                    let message = format_template(template, &values);
+                                    ui.label(&message);
+                                    if ui.small_button("Copy").clicked() {
+                                        ui.ctx().output_mut(|o| o.copied_text = message);
+                                    }
+                                }
+                                Ok(None) => { ui.weak(format!("{} has no message for event {}", ev.source, ev.event_id)); }
+                                Err(e) => { ui.weak(e.as_str()); }
+                            }
+                        });
+                    }
+                    if let Some(bytes) = ev.binary_bytes() {
+                        egui::CollapsingHeader::new(format!("Binary data ({} bytes)", bytes.len())).default_open(true).show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                if ui.button("Copy as bytes").on_hover_text("Space-separated hex bytes").clicked() {
+                                    let text = bytes.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" ");
+                                    ui.ctx().output_mut(|o| o.copied_text = text);
+                                }
+                                if ui.button("Copy dump").clicked() {
+                                    ui.ctx().output_mut(|o| o.copied_text = hex_dump(&bytes));
+                                }
+                                if ui.button("Save as .bin…").clicked() {
+                                    save_binary = Some(bytes.clone());
+                                }
+                            });
+                            ui.monospace(hex_dump(&bytes));
+                        });
+                    }
                     ui.separator();
                     ui.collapsing("Description", |ui| { ui.label(&ev.description); });
-                    ui.collapsing("Raw XML", |ui| { ui.code(&ev.raw_xml); });
+                    ui.collapsing("Raw XML", |ui| {
+                        if ev.raw_xml.is_empty() && !self.settings.keep_raw_xml {
+                            ui.label("Raw XML not retained — enable \"Keep raw XML\" in Settings");
+                        } else {
+                            let limit = self.settings.max_xml_display_kb as usize * 1024;
+                            let full = self.full_xml_for.as_ref().is_some_and(|full| full.same_event(ev));
+                            if full || ev.raw_xml.len() <= limit {
+                                ui.code(&ev.raw_xml);
+                            } else {
+                                ui.horizontal(|ui| {
+                                    ui.label(format!("Showing the first {} KB of {} KB.", limit / 1024, ev.raw_xml.len() / 1024));
+                                    if ui.button("Show full").clicked() {
+                                        xml_action = Some(XmlAction::ShowFull);
+                                    }
+                                    if ui.button("Save to file…").clicked() {
+                                        xml_action = Some(XmlAction::Save(ev.raw_xml.clone()));
+                                    }
+                                });
+                                let mut cut = limit;
+                                while !ev.raw_xml.is_char_boundary(cut) {
+                                    cut -= 1;
+                                }
+                                ui.code(&ev.raw_xml[..cut]);
+                            }
+                        }
+                    });
                 } else {
                     ui.label("Select an event to see details");
                 }
+                match xml_action {
+                    Some(XmlAction::ShowFull) => self.full_xml_for = self.selected.and_then(|i| self.filtered_events.get(i)).cloned(),
+                    Some(XmlAction::Save(xml)) => {
+                        if let Some(path) = rfd::FileDialog::new().add_filter("XML", &["xml"]).set_file_name("event.xml").save_file() {
+                            self.status = match std::fs::write(&path, xml) {
+                                Ok(()) => format!("Saved event XML to {}", path.display()),
+                                Err(e) => format!("Export failed: {}", e),
+                            };
+                        }
+                    }
+                    None => {}
+                }
+                if let Some(bytes) = save_binary {
+                    if let Some(path) = rfd::FileDialog::new().add_filter("Binary", &["bin"]).set_file_name("event.bin").save_file() {
+                        self.status = match std::fs::write(&path, bytes) {
+                            Ok(()) => format!("Saved binary data to {}", path.display()),
+                            Err(e) => format!("Export failed: {}", e),
+                        };
+                    }
+                }
             });
         });
+        if let Some(details) = details {
+            self.settings.details_width = details.response.rect.width();
+        }
+        if self.settings.show_minimap && !self.view_rows.is_empty() {
+            egui::SidePanel::right("minimap").resizable(false).exact_width(14.0).show(ctx, |ui| {
+                self.minimap(ui);
+            });
+        }
+
+        let mut toggle_run = None;
+        let mut toggle_day = None;
+        let row_height = self.settings.density.row_height();
+        let mut fetch_more = false;
+        let mut import_requested = false;
+        let mut restore_requested = false;
+        egui::CentralPanel::default().show(ctx, |ui| {
+            // Every selected log failed and nothing else is loaded: explain instead of an empty table
+            if self.file_source.is_none() && self.all_events.is_empty() && !self.load_errors.is_empty() {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(ui.available_height() / 4.0);
+                    ui.heading("No readable system log found");
+                    ui.label("Try running with sufficient privileges, or point the viewer at a log file.");
+                    ui.add_space(8.0);
+                    for error in &self.load_errors {
+                        ui.colored_label(ui.visuals().weak_text_color(), error);
+                    }
+                    ui.add_space(8.0);
+                    import_requested = ui.button("Import a file…").clicked();
+                });
+                return;
+            }
+            if self.restore_offer {
+                ui.horizontal(|ui| {
+                    let sources = &self.settings.last_import_sources;
+                    let names: Vec<&str> = sources.iter()
+                        .map(|s| Path::new(s).file_name().and_then(|n| n.to_str()).unwrap_or(s))
+                        .take(3)
+                        .collect();
+                    let mut text = format!("Last import: {}", names.join(", "));
+                    if sources.len() > names.len() {
+                        text.push_str(&format!(" and {} more", sources.len() - names.len()));
+                    }
+                    ui.label(text).on_hover_text(sources.join("\n"));
+                    if ui.button("Restore last import").clicked() {
+                        restore_requested = true;
+                    }
+                    if ui.button("Dismiss").clicked() {
+                        self.restore_offer = false;
+                    }
+                });
+                ui.separator();
+            }
+            if !self.truncated.is_empty() {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        format!("⚠ Showing newest {} per log from {} — more exist, increase the fetch limit", format_count(self.page_size as usize), self.truncated.join(", ")),
+                    );
+                    fetch_more = ui.button(format!("Fetch {}", format_count(self.page_size as usize * 2))).clicked();
+                });
+                ui.separator();
+            }
+            ui.spacing_mut().button_padding.y = self.settings.density.button_padding();
+            ui.spacing_mut().interact_size.y = row_height - 2.0;
+            // Horizontal only: the table scrolls vertically itself, and inside a vertical
+            // ScrollArea it would be capped in height and build rows outside the view
+            // Keyed by column_layout so Reset columns starts over from the initial widths
+            ui.push_id(self.settings.column_layout, |ui| {
+                egui::ScrollArea::horizontal().show(ui, |ui| {
+                    let columns = self.visible_columns();
+                    let mut table = TableBuilder::new(ui).max_scroll_height(f32::INFINITY).auto_shrink([false; 2]);
+                    for column in &columns {
+                        table = table.column(match column {
+                            TableColumn::Time => Column::auto().resizable(true),
+                            column => Column::initial(column.initial_width()),
+                        });
+                    }
+                    if let Some((row, align)) = self.scroll_to_row.take() {
+                        table = table.scroll_to_row(row, Some(align));
+                    }
+                    let mut first_visible = None;
+                    let mut column_filter_changed = false;
+                    let header_height = if self.show_column_filters { row_height * 2.0 + 4.0 } else { row_height };
+                    table
+                        .striped(true)
+                        .resizable(true)
+                        .header(header_height, |mut header| {
+                            for column in &columns {
+                                header.col(|ui| {
+                                    ui.vertical(|ui| {
+                                        match column.sort_by() {
+                                            Some(sort) => self.sort_header(ui, column.title(), sort),
+                                            None => { ui.label(column.title()); }
+                                        }
+                                        if self.show_column_filters && *column != TableColumn::Delta {
+                                            let text = self.filters.columns.entry(column.clone()).or_default();
+                                            column_filter_changed |= ui.add(egui::TextEdit::singleline(text).hint_text("filter")).changed();
+                                        }
+                                    });
+                                });
+                            }
+                        })
+                        .body(|body| {
+                            body.rows(row_height, self.view_rows.len(), |row_index, mut row| {
+                                first_visible = Some(first_visible.map_or(row_index, |f: usize| f.min(row_index)));
+                                // The lists can shrink while a frame is being built; skip rows that are gone
+                                let Some(view_row) = self.view_rows.get(row_index) else {
+                                    return;
+                                };
+                                let Some(ev) = self.filtered_events.get(view_row.index) else {
+                                    return;
+                                };
+                                if let Some(count) = view_row.day {
+                                    let date = ev.time_created.date_naive();
+                                    for (i, _) in columns.iter().enumerate() {
+                                        row.col(|ui| match i {
+                                            0 => {
+                                                let arrow = if view_row.expanded { "⏷" } else { "⏵" };
+                                                let heading = egui::RichText::new(format!("{} {}", arrow, date.format("%Y-%m-%d %a"))).strong();
+                                                if ui.add(egui::Button::new(heading).frame(false)).clicked() {
+                                                    toggle_day = Some(date);
+                                                }
+                                            }
+                                            1 => { ui.weak(format!("{} events", format_count(count))); }
+                                            _ => {}
+                                        });
+                                    }
+                                    return;
+                                }
+                                let selected = self.selected == Some(view_row.index);
+                                for column in &columns {
+                                    row.col(|ui| match column {
+                                        TableColumn::Time => {
+                                            ui.horizontal(|ui| {
+                                                let label = ui.selectable_label(selected, column.text(ev, &self.time_format));
+                                                if label.clicked() {
+                                                    self.selected = Some(view_row.index);
+                                                    // Picking a row by hand ends Follow
+                                                    self.follow = false;
+                                                }
+                                                if self.baseline.as_ref().is_some_and(|b| !b.keys.contains(&event_key(ev))) {
+                                                    ui.colored_label(ui.visuals().selection.stroke.color, "●").on_hover_text("Not in the baseline");
+                                                }
+                                                if view_row.run > 1 {
+                                                    let arrow = if view_row.expanded { "⏷" } else { "⏵" };
+                                                    if ui.small_button(format!("{} ×{}", arrow, view_row.run)).clicked() {
+                                                        toggle_run = Some(run_key(ev));
+                                                    }
+                                                }
+                                            });
+                                        }
+                                        TableColumn::Level => { ui.colored_label(level_color(&ev.level, ui.visuals()), &ev.level); }
+                                        TableColumn::Delta => {
+                                            let prev = view_row.index.checked_sub(1).and_then(|p| self.filtered_events.get(p));
+                                            ui.label(delta_text(ev, prev));
+                                        }
+                                        TableColumn::Source | TableColumn::User | TableColumn::Computer => {
+                                            let text = column.text(ev, &self.time_format);
+                                            match ellipsize(&text, self.settings.max_cell_chars) {
+                                                Some(short) => { ui.label(short).on_hover_text(text); }
+                                                None => { ui.label(text); }
+                                            }
+                                        }
+                                        column => { ui.label(column.text(ev, &self.time_format)); }
+                                    });
+                                }
+                            });
+                        });
+                    self.first_visible_row = first_visible;
+                    if column_filter_changed {
+                        self.apply_filters();
+                    }
+                });
+            });
+        });
+        if import_requested {
+            self.pick_and_import();
+        }
+        if restore_requested {
+            self.restore_last_import();
+        }
+        if fetch_more {
+            self.page_size = self.page_size.saturating_mul(2);
+            self.reload_logs();
+        }
+        if let Some(date) = toggle_day {
+            if !self.expanded_days.remove(&date) {
+                self.expanded_days.insert(date);
+            }
+            self.rebuild_view_rows();
+        }
+        if let Some(key) = toggle_run {
+            if !self.expanded_runs.remove(&key) {
+                self.expanded_runs.insert(key);
+            }
+            self.rebuild_view_rows();
+        }
+    }
+}
+
+/// Runs `work` on a new thread and sends its message to update_loads
+fn in_background(tx: &Sender<LoadMessage>, work: impl FnOnce() -> LoadMessage + Send + 'static) {
+    let tx = tx.clone();
+    thread::spawn(move || {
+        let _ = tx.send(work());
+    });
+}
+
+/// Time between `ev` and the event before it in the list, in the largest sensible units
+fn delta_text(ev: &EventRecord, prev: Option<&EventRecord>) -> String {
+    let Some(prev) = prev else {
+        return String::new();
+    };
+    let delta = (ev.time_created - prev.time_created).abs();
+    let secs = delta.num_seconds();
+    match secs {
+        0 => format!("{}ms", delta.num_milliseconds()),
+        1..=59 => format!("{:.1}s", delta.num_milliseconds() as f64 / 1000.0),
+        60..=3599 => format!("{}m {:02}s", secs / 60, secs % 60),
+        3600..=86399 => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+        _ => format!("{}d {:02}h", secs / 86400, secs % 86400 / 3600),
+    }
+}
+
+/// Classic 16-bytes-per-line dump: offset, hex bytes, then printable ASCII
+fn hex_dump(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for (line, chunk) in bytes.chunks(16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|b| format!("{:02X}", b)).collect();
+        let ascii: String = chunk.iter().map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' }).collect();
+        out.push_str(&format!("{:08X}  {:<47}  {}\n", line * 16, hex.join(" "), ascii));
+    }
+    out
+}
+
+/// Returns the text surrounding the first occurrence of `needle`, or None if absent
+fn snippet_around(haystack: &str, needle: &str, radius: usize) -> Option<String> {
+    let pos = haystack.find(needle)?;
+    let mut start = pos.saturating_sub(radius);
+    while !haystack.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (pos + needle.len() + radius).min(haystack.len());
+    while !haystack.is_char_boundary(end) {
+        end += 1;
+    }
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    snippet.push_str(&haystack[start..end].replace(['\r', '\n'], " "));
+    if end < haystack.len() {
+        snippet.push('…');
+    }
+    Some(snippet)
+}
+
+/// Background polling loop. Consecutive query failures back off exponentially
+/// up to POLL_MAX_BACKOFF and are reported once per attempt instead of every 2s.
+fn poll_loop(logs: Arc<Mutex<Vec<String>>>, tx: Sender<PollMessage>, min_severity: Arc<AtomicU8>) {
+    let mut failures = 0u32;
+    loop {
+        // Cloned so the UI isn't blocked while wevtutil runs
+        let channels = logs.lock().unwrap().clone();
+        // simple polling: query newest 50 of each selected log
+        let (events, errors) = query_channels(&channels, 50);
+        let threshold = min_severity.load(Ordering::Relaxed);
+        for ev in events.into_iter().rev() {
+            if level_severity(&ev.level) < threshold {
+                continue;
+            }
+            if tx.send(PollMessage::Event(Box::new(ev))).is_err() {
+                return;
+            }
+        }
+        if errors.is_empty() {
+            failures = 0;
+            if tx.send(PollMessage::Heartbeat).is_err() {
+                return;
+            }
+        } else {
+            failures = failures.saturating_add(1);
+            if tx.send(PollMessage::Error(errors.join("; "))).is_err() {
+                return;
+            }
+        }
+        let delay = POLL_INTERVAL
+            .saturating_mul(1 << failures.min(5))
+            .min(POLL_MAX_BACKOFF);
+        thread::sleep(delay);
+    }
+}
+
+/// Byte count in KB, MB or GB with one decimal
+fn format_size(bytes: u64) -> String {
+    let mut size = bytes as f64;
+    for unit in ["bytes", "KB", "MB"] {
+        if size < 1024.0 {
+            return if unit == "bytes" { format!("{} bytes", bytes) } else { format!("{:.1} {}", size, unit) };
+        }
+        size /= 1024.0;
+    }
+    format!("{:.1} GB", size)
+}
+
+/// Size, cap and retention policy of a channel, flagged when it is full
+fn channel_summary(ui: &mut egui::Ui, info: &ChannelInfo) {
+    let size = match (info.file_size, info.max_size) {
+        (Some(size), Some(max)) => format!("{} of {}", format_size(size), format_size(max)),
+        (Some(size), None) => format_size(size),
+        (None, Some(max)) => format!("max {}", format_size(max)),
+        (None, None) => String::new(),
+    };
+    let mut parts = vec![if info.enabled { "Enabled" } else { "Disabled" }.to_string()];
+    parts.extend([size, info.retention_policy().to_string()].into_iter().filter(|p| !p.is_empty()));
+    let mut hover = info.log_file.clone();
+    if let Some(count) = info.record_count {
+        hover.push_str(&format!("\n{} records", format_count(count as usize)));
+    }
+    let text = parts.join(" · ");
+    if info.is_full() {
+        let note = match (info.retention, info.auto_backup) {
+            (_, true) => "full: it is archived and cleared when it fills",
+            (true, false) => "full: new events are being refused",
+            (false, false) => "full: older events have been overwritten",
+        };
+        ui.colored_label(ui.visuals().warn_fg_color, format!("{} ({})", text, note)).on_hover_text(hover);
+    } else {
+        ui.weak(text).on_hover_text(hover);
+    }
+}
+
+/// Formats a count with thousands separators, e.g. 4812 -> "4,812"
+fn format_count(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Parses a comma-separated list of event IDs and inclusive ranges ("4624,4625" or
+/// "4720-4738"); None if any part isn't a valid ID or range
+fn parse_event_ids(text: &str) -> Option<HashSet<u16>> {
+    let mut ids = HashSet::new();
+    for part in text.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((from, to)) => {
+                let (from, to): (u16, u16) = (from.trim().parse().ok()?, to.trim().parse().ok()?);
+                if from > to {
+                    return None;
+                }
+                ids.extend(from..=to);
+            }
+            None => {
+                ids.insert(part.parse().ok()?);
+            }
+        }
+    }
+    Some(ids)
+}
+
+/// Splits a keyword query on whitespace, keeping "quoted phrases" together
+fn keyword_terms(query: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in query.chars() {
+        match c {
+            '"' => {
+                if !current.is_empty() {
+                    terms.push(std::mem::take(&mut current));
+                }
+                in_quotes = !in_quotes;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    terms.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        terms.push(current);
     }
+    terms
+}
+
+/// `text` cut to `max` characters with a trailing "…", or None when it already fits
+fn ellipsize(text: &str, max: usize) -> Option<String> {
+    let (cut, _) = text.char_indices().nth(max)?;
+    let keep = text[..cut].char_indices().nth(max.saturating_sub(1)).map_or(cut, |(i, _)| i);
+    Some(format!("{}…", &text[..keep]))
+}
+
+/// Where the last import is cached when Settings::cache_imports is on
+fn import_cache_path() -> Option<PathBuf> {
+    eframe::storage_dir(APP_NAME).map(|dir| dir.join("last_import.json"))
+}
+
+/// Writes `events` to the import cache on a background thread, replacing the file
+/// only once the new copy is complete
+fn save_import_cache(events: Vec<EventRecord>) {
+    thread::spawn(move || {
+        let Some(path) = import_cache_path() else {
+            return;
+        };
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let partial = path.with_extension("json.partial");
+        let written = File::create(&partial)
+            .map_err(serde_json::Error::io)
+            .and_then(|file| {
+                let mut out = std::io::BufWriter::new(file);
+                serde_json::to_writer(&mut out, &events)?;
+                std::io::Write::flush(&mut out).map_err(serde_json::Error::io)
+            });
+        if written.is_ok() {
+            let _ = std::fs::rename(&partial, &path);
+        }
+    });
+}
+
+/// Appends every .evtx file under `dir` to `out`, descending into subfolders but
+/// not following symlinks. Unreadable folders are skipped.
+fn find_evtx_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(kind) if kind.is_dir() => find_evtx_files(&path, out),
+            Ok(kind) if kind.is_file() && path.extension().is_some_and(|e| e.eq_ignore_ascii_case("evtx")) => out.push(path),
+            _ => {}
+        }
+    }
+}
+
+/// Regex matching `term` literally with a word boundary at each end that is a word
+/// character; `\b` next to punctuation would demand a letter on its other side
+fn whole_word_pattern(term: &str) -> String {
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let start = if is_word(term.chars().next()) { r"\b" } else { "" };
+    let end = if is_word(term.chars().next_back()) { r"\b" } else { "" };
+    format!("{}{}{}", start, regex::escape(term), end)
+}
+
+/// Parses "HH:MM" or "HH:MM:SS"
+fn parse_time_of_day(input: &str) -> Option<NaiveTime> {
+    ["%H:%M:%S", "%H:%M"].iter().find_map(|fmt| NaiveTime::parse_from_str(input.trim(), fmt).ok())
+}
+
+/// Whether `time` falls in the inclusive window; a window whose start is after its end
+/// runs through midnight, and a missing bound is open
+fn in_time_window(time: NaiveTime, from: Option<NaiveTime>, to: Option<NaiveTime>) -> bool {
+    match (from, to) {
+        (Some(from), Some(to)) if from > to => time >= from || time <= to,
+        _ => from.is_none_or(|f| time >= f) && to.is_none_or(|t| time <= t),
+    }
+}
+
+/// Parses a date and time, or a bare time of day meaning today
+fn parse_jump_time(input: &str) -> Option<DateTime<Local>> {
+    let input = input.trim();
+    let naive = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"].iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(input, fmt).ok())
+        .or_else(|| {
+            ["%H:%M:%S", "%H:%M"].iter()
+                .find_map(|fmt| NaiveTime::parse_from_str(input, fmt).ok())
+                .map(|t| Local::now().date_naive().and_time(t))
+        })?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+/// Percent-encodes a value for use in a URL query string
+fn url_encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
 }
 
 fn main() {
     let options = eframe::NativeOptions::default();
-    eframe::run_native(
-        "Rust Windows Event Viewer",
+    if let Err(e) = eframe::run_native(
+        APP_NAME,
         options,
-        Box::new(|_cc| Box::new(EventViewerApp::default())),
-    );
+        Box::new(|cc| {
+            let mut app = EventViewerApp::default();
+            if let Some(settings) = cc.storage.and_then(|s| eframe::get_value(s, SETTINGS_KEY)) {
+                app.settings = settings;
+            }
+            app.apply_settings();
+            app.restore_offer = app.settings.cache_imports
+                && !app.settings.last_import_sources.is_empty()
+                && import_cache_path().is_some_and(|path| path.is_file());
+            // The first load, once the saved query and parsing settings are in effect
+            app.refresh_page();
+            Box::new(app)
+        }),
+    ) {
+        eprintln!("Failed to start event viewer: {}", e);
+    }
 }
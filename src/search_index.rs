@@ -0,0 +1,153 @@
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Bound;
+use crate::event_log::EventRecord;
+
+/// Text of the fields the index covers, copied out of an event so indexing can run
+/// on another thread
+pub type IndexText = [String; 4];
+
+pub fn index_text(ev: &EventRecord) -> IndexText {
+    [ev.description.clone(), ev.raw_xml.clone(), ev.source.clone(), ev.user.clone()]
+}
+
+/// Inverted index from lowercased alphanumeric tokens to the positions of the events containing
+/// them, over every field the keyword filter can search. It narrows a keyword search to
+/// candidate events; callers still check each candidate against the real filter.
+#[derive(Default)]
+pub struct SearchIndex {
+    /// Token to the ascending positions of the events containing it
+    postings: BTreeMap<String, Vec<usize>>,
+    /// Every suffix of a token after its first character, to the tokens ending with it,
+    /// so a term that starts inside a token is still a range lookup
+    suffixes: BTreeMap<String, Vec<String>>,
+    /// Position after the last indexed event
+    end: usize,
+}
+
+impl SearchIndex {
+    /// Indexes `texts` as the events at positions `start..`
+    pub fn build(start: usize, texts: &[IndexText]) -> Self {
+        let mut index = Self { end: start + texts.len(), ..Default::default() };
+        for (i, text) in texts.iter().enumerate() {
+            let idx = start + i;
+            for token in text.iter().flat_map(|field| tokens(field)) {
+                let list = index.postings.entry(token.to_lowercase()).or_default();
+                if list.last() != Some(&idx) {
+                    list.push(idx);
+                }
+            }
+        }
+        let tokens: Vec<String> = index.postings.keys().cloned().collect();
+        for token in tokens {
+            index.add_suffixes(token);
+        }
+        index
+    }
+
+    /// Number of leading events indexed; later ones aren't covered yet
+    pub fn indexed(&self) -> usize {
+        self.end
+    }
+
+    /// Adds an index built for the events right after the ones this one covers
+    pub fn merge(&mut self, later: SearchIndex) {
+        if self.end == 0 {
+            *self = later;
+            return;
+        }
+        for (token, list) in later.postings {
+            match self.postings.get_mut(&token) {
+                Some(existing) => existing.extend(list),
+                None => {
+                    self.postings.insert(token.clone(), list);
+                    self.add_suffixes(token);
+                }
+            }
+        }
+        self.end = later.end;
+    }
+
+    fn add_suffixes(&mut self, token: String) {
+        for (pos, _) in token.char_indices().skip(1) {
+            self.suffixes.entry(token[pos..].to_string()).or_default().push(token.clone());
+        }
+    }
+
+    /// Indexed events that may contain `term` as a substring in any letter case, or None when
+    /// the term has no alphanumeric characters to look up and every event is a candidate
+    pub fn candidates(&self, term: &str) -> Option<HashSet<usize>> {
+        let mut result: Option<HashSet<usize>> = None;
+        let term = term.to_lowercase();
+        for piece in tokens(&term) {
+            // Tokens starting with the piece, then tokens with the piece further in
+            let mut hits: HashSet<usize> = prefixed(&self.postings, piece).flat_map(|list| list.iter().copied()).collect();
+            for token in prefixed(&self.suffixes, piece).flatten() {
+                hits.extend(self.postings.get(token).into_iter().flatten().copied());
+            }
+            result = Some(match result {
+                Some(prev) => prev.intersection(&hits).copied().collect(),
+                None => hits,
+            });
+        }
+        result
+    }
+}
+
+/// Values of the keys in `map` that start with `prefix`
+fn prefixed<'a, V>(map: &'a BTreeMap<String, V>, prefix: &'a str) -> impl Iterator<Item = &'a V> {
+    map.range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+        .take_while(move |(key, _)| key.starts_with(prefix))
+        .map(|(_, value)| value)
+}
+
+fn tokens(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric()).filter(|t| !t.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(description: &str) -> IndexText {
+        [description.to_string(), String::new(), String::new(), String::new()]
+    }
+
+    fn sorted(hits: Option<HashSet<usize>>) -> Vec<usize> {
+        let mut hits: Vec<usize> = hits.expect("term has tokens").into_iter().collect();
+        hits.sort_unstable();
+        hits
+    }
+
+    #[test]
+    fn syncs_appended_and_replaced_events() {
+        let mut index = SearchIndex::build(0, &[text("Logon failed"), text("Service started")]);
+        assert_eq!(index.indexed(), 2);
+        index.merge(SearchIndex::build(2, &[text("logon succeeded"), text("Service stopped")]));
+        assert_eq!(index.indexed(), 4);
+        assert_eq!(sorted(index.candidates("LOGON")), vec![0, 2]);
+        assert_eq!(sorted(index.candidates("service")), vec![1, 3]);
+        // Substrings inside a token, and a token first seen in the appended events
+        assert_eq!(sorted(index.candidates("ogo")), vec![0, 2]);
+        assert_eq!(sorted(index.candidates("cceed")), vec![2]);
+
+        // Replaced events are indexed from scratch, leaving nothing of the old ones
+        let index = SearchIndex::build(0, &[text("Disk full")]);
+        assert_eq!(index.indexed(), 1);
+        assert!(sorted(index.candidates("logon")).is_empty());
+        assert_eq!(sorted(index.candidates("disk")), vec![0]);
+    }
+
+    #[test]
+    fn intersects_the_pieces_of_a_term() {
+        let index = SearchIndex::build(0, &[
+            text("An account failed to log on"),
+            text("An account was logged off"),
+            text("Audit failed"),
+        ]);
+        assert_eq!(sorted(index.candidates("account failed")), vec![0]);
+        assert_eq!(sorted(index.candidates("fail")), vec![0, 2]);
+        assert_eq!(sorted(index.candidates("log")), vec![0, 1]);
+        assert!(sorted(index.candidates("account audit")).is_empty());
+        assert_eq!(index.candidates("--"), None);
+    }
+}
@@ -0,0 +1,23 @@
+/// Plays a short alert tone without blocking the UI; Critical gets a higher pitch than Error.
+/// The audio backend is only built on Windows, so elsewhere this does nothing.
+pub fn play_alert(level: &str) {
+    #[cfg(target_os = "windows")]
+    {
+        use rodio::Source;
+        let freq = if level == "Critical" { 1320.0 } else { 880.0 };
+        std::thread::spawn(move || {
+            let Ok((_stream, handle)) = rodio::OutputStream::try_default() else {
+                return;
+            };
+            let Ok(sink) = rodio::Sink::try_new(&handle) else {
+                return;
+            };
+            sink.append(rodio::source::SineWave::new(freq).take_duration(std::time::Duration::from_millis(250)).amplify(0.2));
+            sink.sleep_until_end();
+        });
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = level;
+    }
+}
@@ -0,0 +1,92 @@
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, TimeZone};
+use regex::Regex;
+use crate::event_log::EventRecord;
+
+/// Built-in line patterns offered in Settings, as (name, regex)
+pub const TEXT_IMPORT_PRESETS: [(&str, &str); 2] = [
+    (
+        "Syslog",
+        r"^(?P<time>\w{3} +\d{1,2} \d{2}:\d{2}:\d{2}) \S+ (?P<source>[^:\[]+)(?:\[\d+\])?: (?P<message>.*)$",
+    ),
+    (
+        "Apache combined",
+        r#"^(?P<source>\S+) \S+ \S+ \[(?P<time>[^\]]+)\] "(?P<message>[^"]*)" (?P<status>\d{3})"#,
+    ),
+];
+
+/// Turns each line of `contents` into an event using the named groups `time`,
+/// `level`, `source` and `message` of `pattern`. Lines that don't match are
+/// kept as Info events carrying only the line as their description.
+pub fn parse_text_log(contents: &str, pattern: &Regex, log_name: &str) -> Vec<EventRecord> {
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let mut record = EventRecord {
+                log_name: log_name.to_string(),
+                level: "Info".to_string(),
+                description: line.to_string(),
+                raw_xml: line.to_string(),
+                ..Default::default()
+            };
+            let Some(caps) = pattern.captures(line) else {
+                return record;
+            };
+            if let Some(time) = caps.name("time").and_then(|m| parse_log_time(m.as_str())) {
+                record.time_created = time;
+            }
+            if let Some(level) = caps.name("level") {
+                record.level = normalize_level(level.as_str());
+            } else if let Some(status) = caps.name("status") {
+                record.level = http_status_level(status.as_str()).to_string();
+            }
+            if let Some(source) = caps.name("source") {
+                record.source = source.as_str().trim().to_string();
+            }
+            if let Some(message) = caps.name("message") {
+                record.description = message.as_str().to_string();
+            }
+            record
+        })
+        .collect()
+}
+
+/// Maps the level spellings common in text logs onto the viewer's level names
+fn normalize_level(level: &str) -> String {
+    match level.to_ascii_lowercase().as_str() {
+        "crit" | "critical" | "fatal" | "emerg" | "alert" => "Critical".to_string(),
+        "err" | "error" => "Error".to_string(),
+        "warn" | "warning" => "Warning".to_string(),
+        "info" | "information" | "notice" => "Information".to_string(),
+        "debug" | "trace" | "verbose" => "Verbose".to_string(),
+        _ => level.to_string(),
+    }
+}
+
+/// Server errors as Error, client errors as Warning, everything else as Information
+fn http_status_level(status: &str) -> &'static str {
+    match status.as_bytes().first() {
+        Some(b'5') => "Error",
+        Some(b'4') => "Warning",
+        _ => "Information",
+    }
+}
+
+/// Accepts RFC 3339, "YYYY-MM-DD HH:MM:SS", Apache's "10/Oct/2000:13:55:36 -0700"
+/// and year-less syslog stamps, which are assumed to be from this year
+fn parse_log_time(text: &str) -> Option<DateTime<Local>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(text) {
+        return Some(dt.with_timezone(&Local));
+    }
+    if let Ok(dt) = DateTime::parse_from_str(text, "%d/%b/%Y:%H:%M:%S %z") {
+        return Some(dt.with_timezone(&Local));
+    }
+    let naive = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f"))
+        .or_else(|_| {
+            let with_year = format!("{} {}", Local::now().year(), text);
+            NaiveDateTime::parse_from_str(&with_year, "%Y %b %e %H:%M:%S")
+        })
+        .ok()?;
+    Local.from_local_datetime(&naive).earliest()
+}
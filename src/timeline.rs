@@ -0,0 +1,69 @@
+use chrono::{DateTime, Local, TimeZone};
+use crate::event_log::EventRecord;
+
+/// Level columns broken out per bucket; anything else is counted as "Other"
+pub const TIMELINE_LEVELS: [&str; 5] = ["Critical", "Error", "Warning", "Information", "Verbose"];
+
+/// Most buckets timeline_buckets returns, so one stray timestamp (such as a 1970 fallback)
+/// can't ask for millions of them
+pub const MAX_BUCKETS: i64 = 10_000;
+
+/// Event counts for one fixed-width slice of time
+pub struct TimelineBucket {
+    pub start: DateTime<Local>,
+    pub total: usize,
+    /// Counts in TIMELINE_LEVELS order, followed by Other
+    pub per_level: [usize; 6],
+}
+
+/// Buckets `events` into `bucket_secs`-wide slices from the oldest to the newest
+/// event, including empty buckets so gaps stay visible. Buckets are widened when the
+/// span would otherwise need more than MAX_BUCKETS of them.
+pub fn timeline_buckets(events: &[EventRecord], bucket_secs: i64) -> Vec<TimelineBucket> {
+    let times = || events.iter().map(|ev| ev.time_created.timestamp());
+    let (Some(oldest), Some(newest)) = (times().min(), times().max()) else {
+        return Vec::new();
+    };
+    let bucket_secs = bucket_secs.max(1).max((newest - oldest) / (MAX_BUCKETS - 2) + 1);
+    let slot = |ev: &EventRecord| ev.time_created.timestamp().div_euclid(bucket_secs);
+    let (first, last) = (oldest.div_euclid(bucket_secs), newest.div_euclid(bucket_secs));
+    let mut buckets: Vec<TimelineBucket> = (first..=last)
+        .map(|s| TimelineBucket {
+            start: Local.timestamp_opt(s * bucket_secs, 0).unwrap(),
+            total: 0,
+            per_level: [0; 6],
+        })
+        .collect();
+    for ev in events {
+        let bucket = &mut buckets[(slot(ev) - first) as usize];
+        bucket.total += 1;
+        let level = TIMELINE_LEVELS.iter().position(|l| *l == ev.level).unwrap_or(TIMELINE_LEVELS.len());
+        bucket.per_level[level] += 1;
+    }
+    buckets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> EventRecord {
+        EventRecord { time_created: Local.timestamp_opt(secs, 0).unwrap(), level: "Error".to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn widens_buckets_for_a_1970_outlier() {
+        let now = 1_700_000_000;
+        let events = [at(0), at(now), at(now + 30)];
+        let buckets = timeline_buckets(&events, 60);
+        assert!(buckets.len() as i64 <= MAX_BUCKETS);
+        assert_eq!(buckets.iter().map(|b| b.total).sum::<usize>(), 3);
+        assert_eq!(buckets[0].total, 1);
+        assert_eq!(buckets.last().unwrap().per_level[1], 2);
+
+        // A span that fits keeps the requested width
+        let buckets = timeline_buckets(&events[1..], 60);
+        assert_eq!(buckets.len(), 1);
+        assert_eq!(buckets[0].start.timestamp(), now / 60 * 60);
+    }
+}